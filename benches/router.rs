@@ -0,0 +1,85 @@
+//! Compares dispatching with a `Router` to an equivalent `or` chain.
+//!
+//! Run with `cargo bench --bench router`.
+
+extern crate futures;
+extern crate hyper;
+extern crate warp;
+
+use std::time::{Duration, Instant};
+
+use futures::Future;
+use hyper::{Body, Request};
+use hyper::service::Service;
+
+use warp::Filter;
+use warp::filters::BoxedFilter;
+use warp::http::Method;
+
+const ROUTES: usize = 300;
+const ITERS: u32 = 10_000;
+
+fn router() -> warp::router::Router {
+    (0..ROUTES).fold(warp::router(), |router, i| {
+        let template = format!("/api/r{}/items/{{id}}", i);
+        router.route(Method::GET, &template, warp::router::param("id").map(|id: u32| {
+            id.to_string()
+        }))
+    })
+}
+
+fn or_chain() -> BoxedFilter<(String,)> {
+    let route = |i: usize| {
+        // `path` wants a &'static str, as most routes are string literals.
+        let name: &'static str = Box::leak(format!("r{}", i).into_boxed_str());
+        warp::path("api")
+            .and(warp::path(name))
+            .and(warp::path("items"))
+            .and(warp::path::param())
+            .and(warp::path::end())
+            .and(warp::get2())
+            .map(|id: u32| id.to_string())
+            .boxed()
+    };
+    (1..ROUTES).fold(route(0), |chain, i| {
+        chain
+            .or(route(i))
+            .unify()
+            .boxed()
+    })
+}
+
+fn bench<S>(name: &str, path: &str, svc: &mut S)
+where
+    S: Service<ReqBody=Body>,
+    S::Error: ::std::fmt::Debug,
+{
+    let mut elapsed = Duration::from_secs(0);
+    for _ in 0..ITERS {
+        let req = Request::get(path)
+            .body(Body::empty())
+            .unwrap();
+        let start = Instant::now();
+        let res = svc.call(req).wait().unwrap();
+        elapsed += start.elapsed();
+        assert_eq!(res.status(), 200);
+    }
+    println!("{:<20} {:>10} ns/iter", name, (elapsed / ITERS).subsec_nanos());
+}
+
+fn main() {
+    // Both filters are ready immediately, so their services can be driven
+    // without a runtime.
+    let mut router = warp::service(router());
+    let mut chain = warp::service(or_chain());
+
+    println!("{} routes", ROUTES);
+    for &(pos, path) in &[
+        ("first", "/api/r0/items/1"),
+        ("middle", "/api/r150/items/1"),
+        ("last", "/api/r299/items/1"),
+    ] {
+        bench(&format!("router ({})", pos), path, &mut router);
+        bench(&format!("or chain ({})", pos), path, &mut chain);
+    }
+}
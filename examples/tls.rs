@@ -0,0 +1,14 @@
+#![deny(warnings)]
+extern crate warp;
+
+use warp::Filter;
+
+fn main() {
+    // Match any request and return hello world!
+    let routes = warp::any()
+        .map(|| "Hello, World!");
+
+    warp::serve(routes)
+        .tls("examples/tls/cert.pem", "examples/tls/key.rsa")
+        .run(([127, 0, 0, 1], 3030));
+}
@@ -3,6 +3,7 @@ use std::mem;
 use futures::{Async, Future, Poll};
 
 use ::reject::CombineRejection;
+use ::routes::Description;
 use super::{Combine, FilterBase, Filter, HList, Tuple};
 
 #[derive(Clone, Copy, Debug)]
@@ -29,6 +30,10 @@ where
             state: State::First(self.first.filter(), self.second.clone()),
         }
     }
+
+    fn description(&self) -> Description {
+        Description::and(self.first.description(), self.second.description())
+    }
 }
 
 #[allow(missing_debug_implementations)]
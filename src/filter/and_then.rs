@@ -3,6 +3,7 @@ use std::mem;
 use futures::{Async, Future, IntoFuture, Poll};
 
 use ::reject::CombineRejection;
+use ::routes::Description;
 use super::{FilterBase, Filter, Func};
 
 #[derive(Clone, Copy, Debug)]
@@ -28,6 +29,11 @@ where
             state: State::First(self.filter.filter(), self.callback.clone()),
         }
     }
+
+    fn description(&self) -> Description {
+        // The callback may reject.
+        Description::and(self.filter.description(), Description::Opaque)
+    }
 }
 
 #[allow(missing_debug_implementations)]
@@ -4,6 +4,7 @@ use std::sync::Arc;
 use futures::Future;
 
 use ::reject::{Rejection};
+use ::routes::Description;
 use super::{FilterBase, Filter, Tuple};
 
 /// A type representing a boxed `Filter` trait object.
@@ -59,7 +60,8 @@ impl<T: Tuple> Clone for BoxedFilter<T> {
 
 impl<T: Tuple> fmt::Debug for BoxedFilter<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.debug_struct("BoxedFilter")
+        f.debug_tuple("BoxedFilter")
+            .field(&self.filter.description())
             .finish()
     }
 }
@@ -77,6 +79,10 @@ impl<T: Tuple + Send> FilterBase for BoxedFilter<T> {
     fn filter(&self) -> Self::Future {
         self.filter.filter()
     }
+
+    fn description(&self) -> Description {
+        self.filter.description()
+    }
 }
 
 struct BoxingFilter<F> {
@@ -95,4 +101,8 @@ where
     fn filter(&self) -> Self::Future {
         Box::new(self.filter.filter())
     }
+
+    fn description(&self) -> Description {
+        self.filter.description()
+    }
 }
@@ -0,0 +1,41 @@
+use ::routes::Description;
+use super::{FilterBase, Filter};
+
+// Adds a `Description` to a filter, usually a `FilterFn`, which can't
+// describe itself.
+pub(crate) fn describe<F, D>(filter: F, describe: D) -> Described<F, D>
+where
+    F: Filter,
+    D: Fn() -> Description,
+{
+    Described {
+        filter,
+        describe,
+    }
+}
+
+#[derive(Clone, Copy)]
+#[allow(missing_debug_implementations)]
+pub(crate) struct Described<F, D> {
+    filter: F,
+    describe: D,
+}
+
+impl<F, D> FilterBase for Described<F, D>
+where
+    F: Filter,
+    D: Fn() -> Description,
+{
+    type Extract = F::Extract;
+    type Error = F::Error;
+    type Future = F::Future;
+
+    #[inline]
+    fn filter(&self) -> Self::Future {
+        self.filter.filter()
+    }
+
+    fn description(&self) -> Description {
+        (self.describe)()
+    }
+}
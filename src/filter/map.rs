@@ -1,5 +1,6 @@
 use futures::{Async, Future, Poll};
 
+use ::routes::Description;
 use super::{FilterBase, Filter, Func};
 
 #[derive(Clone, Copy, Debug)]
@@ -23,6 +24,10 @@ where
             callback: self.callback.clone(),
         }
     }
+
+    fn description(&self) -> Description {
+        self.filter.description()
+    }
 }
 
 #[allow(missing_debug_implementations)]
@@ -1,6 +1,7 @@
 use futures::{Future, Poll};
 
 use ::reject::Reject;
+use ::routes::Description;
 use super::{FilterBase, Filter};
 
 #[derive(Clone, Copy, Debug)]
@@ -25,6 +26,10 @@ where
             callback: self.callback.clone(),
         }
     }
+
+    fn description(&self) -> Description {
+        self.filter.description()
+    }
 }
 
 #[allow(missing_debug_implementations)]
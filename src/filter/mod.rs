@@ -1,30 +1,39 @@
 mod and;
 mod and_then;
 mod boxed;
+mod describe;
 mod map;
 mod map_err;
 mod or;
 mod or_else;
 mod recover;
 mod service;
+mod timeout;
 mod unify;
 mod untuple_one;
 mod wrap;
 
+use std::time::Duration;
+
 use futures::{future, Future, IntoFuture};
 
 pub(crate) use ::generic::{Combine, Either, Func, HList, One, one, Tuple};
 use ::reject::{CombineRejection, Reject, Rejection};
 use ::route::{self, Route};
+use ::routes::Description;
 
 pub(crate) use self::and::And;
 use self::and_then::AndThen;
 pub use self::boxed::BoxedFilter;
+pub(crate) use self::describe::describe;
 pub(crate) use self::map::Map;
 pub(crate) use self::map_err::MapErr;
 pub(crate) use self::or::Or;
 use self::or_else::OrElse;
 use self::recover::Recover;
+pub use self::service::{service, FilteredService};
+pub(crate) use self::timeout::Deadline;
+use self::timeout::Timeout;
 use self::unify::Unify;
 use self::untuple_one::UntupleOne;
 pub(crate) use self::wrap::{WrapSealed, Wrap};
@@ -38,6 +47,10 @@ pub trait FilterBase {
 
     fn filter(&self) -> Self::Future;
 
+    fn description(&self) -> Description {
+        Description::Opaque
+    }
+
     // crate-private for now
 
     fn map_err<F, E>(self, fun: F) -> MapErr<Self, F>
@@ -272,6 +285,39 @@ pub trait Filter: FilterBase {
         }
     }
 
+    /// Rejects the request if this `Filter` doesn't complete within the
+    /// `duration`.
+    ///
+    /// The timer starts when this filter starts filtering a request, and
+    /// stops once it has extracted its value. If the timer expires first,
+    /// the request is rejected with a [`TimedOut`](::reject::TimedOut)
+    /// cause, which is a `408 Request Timeout` if the request body was
+    /// still being read, and otherwise a `503 Service Unavailable`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use warp::Filter;
+    ///
+    /// let route = warp::path("slow")
+    ///     .and_then(|| {
+    ///         // Something that may take a long time...
+    ///         Ok::<_, warp::Rejection>("done!")
+    ///     })
+    ///     .timeout(Duration::from_secs(10));
+    /// ```
+    fn timeout(self, duration: Duration) -> Timeout<Self>
+    where
+        Self: Sized,
+        Rejection: From<Self::Error>,
+    {
+        Timeout {
+            filter: self,
+            duration,
+        }
+    }
+
     /// Unifies the extracted value of `Filter`s composed with `or`.
     ///
     /// When a `Filter` extracts some `Either<T, T>`, where both sides
@@ -374,6 +420,26 @@ pub trait Filter: FilterBase {
         wrapper.wrap(self)
     }
 
+    /// Describes what this `Filter` matches, such as path segments, methods,
+    /// and required headers.
+    ///
+    /// See the [`routes`](::routes) module to list or check the routes of a
+    /// whole filter tree.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    /// use warp::routes::Description;
+    ///
+    /// let hello = warp::path("hello");
+    ///
+    /// assert_eq!(hello.describe(), Description::Path("hello".into()));
+    /// ```
+    fn describe(&self) -> Description {
+        self.description()
+    }
+
     /// Boxes this filter into a trait object, making it easier to name the type.
     ///
     /// # Example
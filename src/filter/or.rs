@@ -4,7 +4,8 @@ use futures::{Async, Future, Poll};
 
 use ::generic::Either;
 use ::reject::CombineRejection;
-use ::route;
+use ::route::Snapshot;
+use ::routes::Description;
 use super::{FilterBase, Filter};
 
 #[derive(Clone, Copy, Debug)]
@@ -27,18 +28,22 @@ where
     type Future = EitherFuture<T, U>;
 
     fn filter(&self) -> Self::Future {
-        let idx = route::with(|route| route.matched_path_index());
+        let original = Snapshot::take();
         EitherFuture {
             state: State::First(self.first.filter(), self.second.clone()),
-            original_path_index: PathIndex(idx),
+            original,
         }
     }
+
+    fn description(&self) -> Description {
+        Description::or(self.first.description(), self.second.description())
+    }
 }
 
 #[allow(missing_debug_implementations)]
 pub struct EitherFuture<T: Filter, U: Filter> {
     state: State<T, U>,
-    original_path_index: PathIndex,
+    original: Snapshot,
 }
 
 enum State<T: Filter, U: Filter> {
@@ -47,16 +52,6 @@ enum State<T: Filter, U: Filter> {
     Done,
 }
 
-struct PathIndex(usize);
-
-impl PathIndex {
-    fn reset_path(&self) {
-        route::with(|route| {
-            route.reset_matched_path_index(self.0)
-        });
-    }
-}
-
 impl<T, U> Future for EitherFuture<T, U>
 where
     T: Filter,
@@ -80,7 +75,7 @@ where
                 Ok(Async::NotReady) => Ok(Async::NotReady),
 
                 Err(e) => {
-                    self.original_path_index.reset_path();
+                    self.original.reset();
                     let err1 = err1
                         .take()
                         .expect("polled after complete");
@@ -90,7 +85,7 @@ where
             State::Done => panic!("polled after complete"),
         };
 
-        self.original_path_index.reset_path();
+        self.original.reset();
 
         let mut second = match mem::replace(&mut self.state, State::Done) {
             State::First(_, second) => second.filter(),
@@ -106,7 +101,7 @@ where
                 Ok(Async::NotReady)
             }
             Err(e) => {
-                self.original_path_index.reset_path();
+                self.original.reset();
                 return Err(e.combine(err1));
             }
         }
@@ -2,7 +2,8 @@ use std::mem;
 
 use futures::{Async, Future, IntoFuture, Poll};
 
-use ::route;
+use ::route::Snapshot;
+use ::routes::Description;
 use super::{FilterBase, Filter, Func};
 
 #[derive(Clone, Copy, Debug)]
@@ -23,12 +24,16 @@ where
     type Future = OrElseFuture<T, F>;
     #[inline]
     fn filter(&self) -> Self::Future {
-        let idx = route::with(|route| route.matched_path_index());
+        let original = Snapshot::take();
         OrElseFuture {
             state: State::First(self.filter.filter(), self.callback.clone()),
-            original_path_index: PathIndex(idx),
+            original,
         }
     }
+
+    fn description(&self) -> Description {
+        self.filter.description()
+    }
 }
 
 #[allow(missing_debug_implementations)]
@@ -40,7 +45,7 @@ where
     <F::Output as IntoFuture>::Future: Send,
 {
     state: State<T, F>,
-    original_path_index: PathIndex,
+    original: Snapshot,
 }
 
 enum State<T, F>
@@ -55,16 +60,6 @@ where
     Done,
 }
 
-struct PathIndex(usize);
-
-impl PathIndex {
-    fn reset_path(&self) {
-        route::with(|route| {
-            route.reset_matched_path_index(self.0)
-        });
-    }
-}
-
 impl<T, F> Future for OrElseFuture<T, F>
 where
     T: Filter,
@@ -88,7 +83,7 @@ where
             State::Done => panic!("polled after complete"),
         };
 
-        self.original_path_index.reset_path();
+        self.original.reset();
 
         let mut second = match mem::replace(&mut self.state, State::Done) {
             State::First(_, second) => second.call(err).into_future(),
@@ -1,31 +1,102 @@
-use futures::{Future, Poll};
+use std::time::Duration;
+
+use futures::{future, Async, Future, IntoFuture, Poll};
+use hyper::Body;
+use hyper::service::Service as HyperService;
+use tower_service::Service as TowerService;
 
 use ::{Filter, Request};
-use ::reject::Reject;
-use ::reply::{Reply};
+use ::never::Never;
+use ::reject::{Reject, Rejection};
+use ::reply::{Reply, ReplySealed, Response};
 use ::route::{self, Route};
 use ::server::{IntoWarpService, WarpService};
+use ::transport::ConnInfo;
+use super::Deadline;
+
+/// Convert a `Filter` into a `Service`.
+///
+/// The returned [`FilteredService`](FilteredService) implements both
+/// hyper's and tower's `Service` traits, so a warp filter can be mounted
+/// inside an existing hyper server, or put behind tower middleware.
+///
+/// Filters are run the same way as by a warp `Server`, with rejections
+/// turned into error responses. Since the connection isn't known, the
+/// [`addr`](::addr) filters extract `None`.
+///
+/// # Example
+///
+/// ```no_run
+/// extern crate hyper;
+/// extern crate warp;
+///
+/// use warp::Filter;
+///
+/// # fn main() {
+/// let routes = warp::any()
+///     .map(|| "Hello, World!");
+///
+/// let svc = warp::service(routes);
+///
+/// let server = hyper::Server::bind(&([127, 0, 0, 1], 3030).into())
+///     .serve(move || svc);
+///
+/// # drop(server);
+/// # }
+/// ```
+pub fn service<F>(filter: F) -> FilteredService<F>
+where
+    F: Filter,
+    <F::Future as Future>::Item: Reply,
+    <F::Future as Future>::Error: Reject,
+    Rejection: From<F::Error>,
+{
+    FilteredService {
+        filter,
+    }
+}
 
+/// A `Service` created from a `Filter`, with [`warp::service`](service).
 #[derive(Copy, Clone, Debug)]
 pub struct FilteredService<F> {
     filter: F,
 }
 
+impl<F> FilteredService<F>
+where
+    F: Filter,
+    <F::Future as Future>::Item: Reply,
+    <F::Future as Future>::Error: Reject,
+    Rejection: From<F::Error>,
+{
+    #[inline]
+    fn call_route(&self, req: Request) -> ResponseFuture<F::Future> {
+        ResponseFuture {
+            inner: WarpService::call(self, req, ConnInfo::default(), None),
+        }
+    }
+}
+
 impl<F> WarpService for FilteredService<F>
 where
     F: Filter,
     <F::Future as Future>::Item: Reply,
     <F::Future as Future>::Error: Reject,
+    Rejection: From<F::Error>,
 {
     type Reply = FilteredFuture<F::Future>;
 
     #[inline]
-    fn call(&self, req: Request) -> Self::Reply {
+    fn call(&self, req: Request, conn_info: ConnInfo, timeout: Option<Duration>) -> Self::Reply {
         debug_assert!(!route::is_set(), "nested route::set calls");
 
-        let route = Route::new(req);
+        let route = Route::new(req, conn_info);
+        if timeout.is_some() {
+            route.borrow_mut().track_body();
+        }
         let fut = route::set(&route, || self.filter.filter());
         FilteredFuture {
+            deadline: timeout.map(Deadline::new),
             future: fut,
             route: route,
         }
@@ -34,6 +105,7 @@ where
 
 #[derive(Debug)]
 pub struct FilteredFuture<F> {
+    deadline: Option<Deadline>,
     future: F,
     route: ::std::cell::RefCell<Route>,
 }
@@ -41,16 +113,117 @@ pub struct FilteredFuture<F> {
 impl<F> Future for FilteredFuture<F>
 where
     F: Future,
+    Rejection: From<F::Error>,
 {
     type Item = F::Item;
-    type Error = F::Error;
+    type Error = Rejection;
 
     #[inline]
     fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
         debug_assert!(!route::is_set(), "nested route::set calls");
 
         let fut = &mut self.future;
-        route::set(&self.route, || fut.poll())
+        let deadline = &mut self.deadline;
+        route::set(&self.route, || {
+            match fut.poll() {
+                Ok(Async::NotReady) => (),
+                ready => return ready.map_err(Rejection::from),
+            }
+
+            if let Some(ref mut deadline) = *deadline {
+                if deadline.poll_expired() {
+                    return Err(deadline.rejection());
+                }
+            }
+            Ok(Async::NotReady)
+        })
+    }
+}
+
+impl<F> HyperService for FilteredService<F>
+where
+    F: Filter,
+    <F::Future as Future>::Item: Reply,
+    <F::Future as Future>::Error: Reject,
+    Rejection: From<F::Error>,
+{
+    type ReqBody = Body;
+    type ResBody = Body;
+    type Error = Never;
+    type Future = ResponseFuture<F::Future>;
+
+    #[inline]
+    fn call(&mut self, req: Request) -> Self::Future {
+        self.call_route(req)
+    }
+}
+
+impl<F> TowerService<Request> for FilteredService<F>
+where
+    F: Filter,
+    <F::Future as Future>::Item: Reply,
+    <F::Future as Future>::Error: Reject,
+    Rejection: From<F::Error>,
+{
+    type Response = Response;
+    type Error = Never;
+    type Future = ResponseFuture<F::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self) -> Poll<(), Self::Error> {
+        Ok(Async::Ready(()))
+    }
+
+    #[inline]
+    fn call(&mut self, req: Request) -> Self::Future {
+        self.call_route(req)
+    }
+}
+
+// Allows passing a `FilteredService` where hyper expects a "new service",
+// such as in a `|| svc` closure.
+impl<F> IntoFuture for FilteredService<F>
+where
+    F: Filter,
+    <F::Future as Future>::Item: Reply,
+    <F::Future as Future>::Error: Reject,
+    Rejection: From<F::Error>,
+{
+    type Future = future::FutureResult<Self, Never>;
+    type Item = Self;
+    type Error = Never;
+
+    #[inline]
+    fn into_future(self) -> Self::Future {
+        future::ok(self)
+    }
+}
+
+/// The `Future` of a `FilteredService`, resolving to a `Response`.
+#[derive(Debug)]
+pub struct ResponseFuture<F> {
+    inner: FilteredFuture<F>,
+}
+
+impl<F> Future for ResponseFuture<F>
+where
+    F: Future,
+    F::Item: Reply,
+    Rejection: From<F::Error>,
+{
+    type Item = Response;
+    type Error = Never;
+
+    #[inline]
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        match self.inner.poll() {
+            Ok(Async::Ready(ok)) => Ok(Async::Ready(ok.into_response())),
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Err(err) => {
+                debug!("rejected: {:?}", err);
+                Ok(Async::Ready(err.into_response()))
+            }
+        }
     }
 }
 
@@ -59,6 +232,7 @@ where
     F: Filter + Send + Sync + 'static,
     <F::Future as Future>::Item: Reply,
     <F::Future as Future>::Error: Reject,
+    Rejection: From<F::Error>,
 {
     type Service = FilteredService<F>;
 
@@ -73,6 +247,7 @@ where
     F: Filter + Send + Sync + 'static,
     <F::Future as Future>::Item: Reply,
     <F::Future as Future>::Error: Reject,
+    Rejection: From<F::Error>,
 {
     type Service = FilteredService<F>;
 
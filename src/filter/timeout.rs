@@ -0,0 +1,107 @@
+use std::time::{Duration, Instant};
+
+use futures::{Async, Future, Poll};
+use tokio::timer::Delay;
+
+use ::reject::{self, Rejection};
+use ::route;
+use ::routes::Description;
+use super::{FilterBase, Filter};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Timeout<T> {
+    pub(super) filter: T,
+    pub(super) duration: Duration,
+}
+
+impl<T> FilterBase for Timeout<T>
+where
+    T: Filter,
+    Rejection: From<T::Error>,
+{
+    type Extract = T::Extract;
+    type Error = Rejection;
+    type Future = TimeoutFuture<T::Future>;
+    #[inline]
+    fn filter(&self) -> Self::Future {
+        route::with(|route| route.track_body());
+        TimeoutFuture {
+            deadline: Deadline::new(self.duration),
+            future: self.filter.filter(),
+        }
+    }
+
+    fn description(&self) -> Description {
+        self.filter.description()
+    }
+}
+
+#[allow(missing_debug_implementations)]
+pub struct TimeoutFuture<F> {
+    deadline: Deadline,
+    future: F,
+}
+
+impl<F> Future for TimeoutFuture<F>
+where
+    F: Future,
+    Rejection: From<F::Error>,
+{
+    type Item = F::Item;
+    type Error = Rejection;
+
+    #[inline]
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        match self.future.poll() {
+            Ok(Async::NotReady) => (),
+            ready => return ready.map_err(Rejection::from),
+        }
+
+        if self.deadline.poll_expired() {
+            Err(self.deadline.rejection())
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
+
+/// A timer that rejects requests taking too long, shared by the `timeout`
+/// filter and the server wide request timeout.
+///
+/// Expects to be polled with a `Route` set.
+#[derive(Debug)]
+pub(crate) struct Deadline {
+    delay: Option<Delay>,
+}
+
+impl Deadline {
+    pub(crate) fn new(duration: Duration) -> Deadline {
+        Deadline {
+            delay: Some(Delay::new(Instant::now() + duration)),
+        }
+    }
+
+    pub(crate) fn poll_expired(&mut self) -> bool {
+        let polled = match self.delay {
+            Some(ref mut delay) => delay.poll(),
+            None => return false,
+        };
+
+        match polled {
+            Ok(Async::Ready(())) => true,
+            Ok(Async::NotReady) => false,
+            Err(err) => {
+                // Without a timer, there's no timeout.
+                error!("request timeout timer error: {}", err);
+                self.delay = None;
+                false
+            }
+        }
+    }
+
+    pub(crate) fn rejection(&self) -> Rejection {
+        let reading_body = route::with(|route| route.is_reading_body());
+        debug!("request timed out, reading body = {}", reading_body);
+        reject::timed_out(reading_body)
+    }
+}
@@ -1,5 +1,6 @@
 use futures::{Async, Future, Poll};
 
+use ::routes::Description;
 use super::{Either, FilterBase, Filter, Tuple};
 
 #[derive(Clone, Copy, Debug)]
@@ -21,6 +22,10 @@ where
             inner: self.filter.filter(),
         }
     }
+
+    fn description(&self) -> Description {
+        self.filter.description()
+    }
 }
 
 #[allow(missing_debug_implementations)]
@@ -0,0 +1,92 @@
+//! Socket Address Filters
+//!
+//! These filters extract the addresses of the connection a request was
+//! received on, and of the listener it was accepted by.
+//!
+//! The addresses are known when serving with [`Server::run`](::Server::run)
+//! or one of the `bind` methods. They are `None` when serving a custom
+//! stream of connections with [`Server::serve_incoming`](::Server::serve_incoming),
+//! or over a Unix Domain Socket.
+
+use std::net::SocketAddr;
+
+use ::filter::{Filter, filter_fn_one, One};
+use ::never::Never;
+
+/// Creates a `Filter` to get the remote address of the connection.
+///
+/// # Example
+///
+/// ```
+/// use std::net::SocketAddr;
+/// use warp::Filter;
+///
+/// let route = warp::addr::remote()
+///     .map(|addr: Option<SocketAddr>| {
+///         println!("remote address = {:?}", addr);
+///         "Hello, World!"
+///     });
+/// ```
+pub fn remote() -> impl Filter<Extract=One<Option<SocketAddr>>, Error=Never> + Copy {
+    filter_fn_one(|route| {
+        Ok::<_, Never>(route.conn_info().remote_addr)
+    })
+}
+
+/// Creates a `Filter` to get the local address the connection was accepted
+/// on.
+///
+/// When listening on an unspecified address, such as `0.0.0.0`, this is
+/// the specific address the client connected to.
+///
+/// # Example
+///
+/// ```
+/// use std::net::SocketAddr;
+/// use warp::Filter;
+///
+/// let route = warp::addr::local()
+///     .map(|addr: Option<SocketAddr>| {
+///         println!("local address = {:?}", addr);
+///         "Hello, World!"
+///     });
+/// ```
+pub fn local() -> impl Filter<Extract=One<Option<SocketAddr>>, Error=Never> + Copy {
+    filter_fn_one(|route| {
+        Ok::<_, Never>(route.conn_info().local_addr)
+    })
+}
+
+/// Creates a `Filter` to get the address of the listener that accepted the
+/// connection.
+///
+/// This is the address the listener was bound to, such as `0.0.0.0:80`,
+/// which tells apart the listeners of a server bound to several addresses
+/// with [`Server::bind_many`](::Server::bind_many).
+///
+/// # Example
+///
+/// ```
+/// use std::net::SocketAddr;
+/// use warp::Filter;
+///
+/// let admin: SocketAddr = ([127, 0, 0, 1], 9090).into();
+///
+/// // Only allow the admin API on the admin listener.
+/// let route = warp::addr::listener()
+///     .and_then(move |addr: Option<SocketAddr>| {
+///         if addr == Some(admin) {
+///             Ok(())
+///         } else {
+///             Err(warp::reject::not_found())
+///         }
+///     })
+///     .untuple_one()
+///     .and(warp::path("admin"))
+///     .map(|| "Hello, admin!");
+/// ```
+pub fn listener() -> impl Filter<Extract=One<Option<SocketAddr>>, Error=Never> + Copy {
+    filter_fn_one(|route| {
+        Ok::<_, Never>(route.conn_info().listener_addr)
+    })
+}
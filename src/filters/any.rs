@@ -4,6 +4,7 @@ use futures::{Future, Poll};
 
 use ::never::Never;
 use ::filter::{FilterBase, Filter};
+use ::routes::Description;
 
 /// A filter that matches any route.
 ///
@@ -60,6 +61,10 @@ impl FilterBase for Any {
     fn filter(&self) -> Self::Future {
         AnyFut
     }
+
+    fn description(&self) -> Description {
+        Description::Any
+    }
 }
 
 #[allow(missing_debug_implementations)]
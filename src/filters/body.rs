@@ -4,39 +4,93 @@
 
 use std::error::Error as StdError;
 use std::fmt;
+use std::io::{self, Write};
+use std::mem;
 
 use bytes::Buf;
 use futures::{Async, Future, Poll, Stream};
 use futures::stream::Concat2;
-use headers::ContentLength;
-use http::header::CONTENT_TYPE;
+use headers::{ContentLength, HeaderMapExt};
+use http::header::{CONTENT_ENCODING, CONTENT_TYPE};
 use hyper::{Body, Chunk};
+use hyper::body::Payload;
 use mime;
 use serde::de::DeserializeOwned;
 use serde_json;
 use serde_urlencoded;
 
 use ::filter::{FilterBase, Filter, filter_fn, filter_fn_one};
-use ::reject::{self, Rejection};
+use ::filters::compression::Encoding;
+use ::filters::multipart::{self, FormOptions};
+use ::reject::{self, PayloadTooLarge, Rejection};
+use ::route::Route;
 
 // Extracts the `Body` Stream from the route.
 //
 // Does not consume any of it.
 pub(crate) fn body() -> impl Filter<Extract=(Body,), Error=Rejection> + Copy {
-    filter_fn_one(|route| {
-        route
-            .take_body()
-            .ok_or_else(|| {
-                error!("request body already taken in previous filter");
-                reject::known(BodyConsumedMultipleTimes(()))
-            })
-    })
+    filter_fn_one(take_body)
+}
+
+pub(crate) fn take_body(route: &mut Route) -> Result<Body, Rejection> {
+    let body = route
+        .take_body()
+        .ok_or_else(|| {
+            error!("request body already taken in previous filter");
+            reject::known(BodyConsumedMultipleTimes(()))
+        })?;
+    if body.is_end_stream() {
+        return Ok(body);
+    }
+
+    let limit = route.body_limit();
+    let body = match Encoding::from_content_encoding(route.headers()) {
+        Ok(Some(encoding)) if route.is_decoding_body() => Body::wrap_stream(DecodeBody {
+            body,
+            decoder: Some(Decoder::new(encoding, limit)),
+        }),
+        _ => body,
+    };
+    match limit {
+        Some(remaining) => Ok(Body::wrap_stream(LimitBody {
+            body,
+            remaining,
+        })),
+        None => Ok(body),
+    }
+}
+
+// Turns an error reading the body into a rejection, which is a
+// `413 Payload Too Large` if a `limit` was passed.
+pub(crate) fn read_error(err: ::hyper::Error) -> Rejection {
+    // The body may be wrapped more than once, such as when decompressed.
+    let mut too_large = false;
+    let mut cause = err.cause2();
+    while let Some(err) = cause {
+        if err.is::<PayloadTooLarge>() {
+            too_large = true;
+            break;
+        }
+        cause = err
+            .downcast_ref::<::hyper::Error>()
+            .and_then(|err| err.cause2());
+    }
+    if too_large {
+        reject::payload_too_large()
+    } else {
+        reject::known(BodyReadError(err))
+    }
 }
 
 /// Require a `content-length` header to have a value no greater than some limit.
 ///
 /// Rejects if `content-length` header is missing, is invalid, or has a number
-/// larger than the limit provided.
+/// larger than the limit provided. To also accept bodies without a
+/// `content-length`, use [`limit`](limit) instead.
+///
+/// The body filters after this one are held to the limit as well, so a
+/// body [`decompress`](decompress)ed from a small `content-length` still
+/// can't grow past it.
 ///
 /// # Example
 ///
@@ -62,6 +116,91 @@ pub fn content_length_limit(limit: u64) -> impl Filter<Extract=(), Error=Rejecti
             }
         })
         .untuple_one()
+        .and(filter_fn(move |route| {
+            route.limit_body(limit);
+            Ok::<_, Rejection>(())
+        }))
+}
+
+/// Require the request body to be no larger than some limit.
+///
+/// Unlike [`content_length_limit`](content_length_limit), this doesn't
+/// need a `content-length` header, so chunked bodies are accepted too. The
+/// bytes are counted as the body filters after this one read them, and
+/// reading fails with a `413 Payload Too Large` once there are too many.
+/// A `content-length` over the limit is still rejected right away.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use warp::Filter;
+///
+/// // Limit the upload to 4kb, with or without a content-length...
+/// let upload = warp::body::limit(4096)
+///     .and(warp::body::json())
+///     .map(|map: HashMap<String, String>| {
+///         "Got a JSON body!"
+///     });
+/// ```
+pub fn limit(limit: u64) -> impl Filter<Extract=(), Error=Rejection> + Copy {
+    filter_fn(move |route| {
+        let length = if route.is_decoding_body() {
+            None
+        } else {
+            route.headers().typed_get()
+        };
+        if let Some(ContentLength(length)) = length {
+            if length > limit {
+                debug!("content-length: {} is over limit {}", length, limit);
+                return Err(reject::payload_too_large());
+            }
+        }
+        route.limit_body(limit);
+        Ok(())
+    })
+}
+
+/// Decompress the request body by its `content-encoding`.
+///
+/// The body filters after this one read the body decompressed, as it
+/// streams in. The `gzip`, `deflate`, and `br` encodings are supported, and
+/// other encodings are rejected with a `415 Unsupported Media Type`.
+///
+/// Use this with a [`limit`](limit) or a
+/// [`content_length_limit`](content_length_limit): either one counts the
+/// decompressed bytes, so a small body that decompresses into a huge one
+/// is stopped early with a `413 Payload Too Large`. Without one, the
+/// decompressed size is unbounded. A `content-length` is the compressed
+/// size, though, so a `limit` after this one doesn't check it.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use warp::Filter;
+///
+/// let route = warp::body::decompress()
+///     .and(warp::body::limit(1024 * 32))
+///     .and(warp::body::json())
+///     .map(|simple_map: HashMap<String, String>| {
+///         "Got a JSON body, maybe gzipped!"
+///     });
+/// ```
+pub fn decompress() -> impl Filter<Extract=(), Error=Rejection> + Copy {
+    filter_fn(|route| {
+        match Encoding::from_content_encoding(route.headers()) {
+            Ok(encoding) => {
+                trace!("decompress body: {:?}", encoding);
+                route.decode_body();
+                Ok(())
+            },
+            Err(()) => {
+                debug!("content-encoding {:?} isn't supported", route.headers().get(CONTENT_ENCODING));
+                Err(reject::unsupported_media_type())
+            },
+        }
+    })
 }
 
 /// Create a `Filter` that extracts the request body as a `futures::Stream`.
@@ -215,6 +354,42 @@ pub fn form<T: DeserializeOwned + Send>() -> impl Filter<Extract=(T,), Error=Rej
         })
 }
 
+/// Returns a `Filter` that matches `multipart/form-data` requests and
+/// extracts a `Stream` of the parts of the body.
+///
+/// Each [`Part`](::multipart::Part) has a name, and maybe a filename and
+/// `content-type`, and is itself a `Stream` of its data. Size limits can be
+/// set on the returned [`FormOptions`](::multipart::FormOptions).
+///
+/// # Warning
+///
+/// This does not have a default size limit, it would be wise to use one to
+/// prevent a overly large request from using too much memory.
+///
+/// # Example
+///
+/// ```
+/// use warp::{Filter, Future, Stream};
+/// use warp::multipart::{FormData, PartData};
+///
+/// let upload = warp::body::multipart()
+///     .max_part_size(1024 * 1024)
+///     .and_then(|form: FormData| {
+///         form.spooled().collect()
+///     })
+///     .map(|parts: Vec<warp::multipart::SpooledPart>| {
+///         for part in parts {
+///             if let PartData::File(file) = part.into_data() {
+///                 println!("uploaded {} bytes to {:?}", file.len(), file.path());
+///             }
+///         }
+///         "Uploaded!"
+///     });
+/// ```
+pub fn multipart() -> FormOptions {
+    multipart::form()
+}
+
 /// The full contents of a request body.
 ///
 /// Extracted with the [`concat`](concat) filter.
@@ -260,7 +435,7 @@ impl Future for Concat {
             Ok(Async::NotReady) => Ok(Async::NotReady),
             Err(err) => {
                 debug!("concat error: {}", err);
-                Err(reject::known(BodyReadError(err)))
+                Err(read_error(err))
             }
         }
     }
@@ -295,6 +470,144 @@ impl fmt::Debug for BodyStream {
     }
 }
 
+// Counts the bytes of a body, failing once there are more than allowed.
+struct LimitBody {
+    body: Body,
+    remaining: u64,
+}
+
+impl Stream for LimitBody {
+    type Item = Chunk;
+    type Error = Box<StdError + Send + Sync>;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let chunk = try_ready!(self.body.poll());
+        if let Some(ref chunk) = chunk {
+            let len = chunk.len() as u64;
+            if len > self.remaining {
+                debug!("request body is over limit");
+                self.remaining = 0;
+                return Err(Box::new(PayloadTooLarge));
+            }
+            self.remaining -= len;
+        }
+        Ok(Async::Ready(chunk))
+    }
+}
+
+// Decompresses a body as it is read.
+struct DecodeBody {
+    body: Body,
+    // Taken once the body is done.
+    decoder: Option<Decoder>,
+}
+
+impl Stream for DecodeBody {
+    type Item = Chunk;
+    type Error = Box<StdError + Send + Sync>;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        loop {
+            let chunk = try_ready!(self.body.poll());
+            let decoded = match self.decoder {
+                Some(ref mut decoder) => match chunk {
+                    Some(ref chunk) => decoder.write(chunk),
+                    None => decoder.finish(),
+                },
+                None => return Ok(Async::Ready(None)),
+            };
+            let decoded = decoded.map_err(|err| -> Box<StdError + Send + Sync> {
+                debug!("request body decode error: {}", err);
+                if err.get_ref().map_or(false, |e| e.is::<PayloadTooLarge>()) {
+                    Box::new(PayloadTooLarge)
+                } else {
+                    Box::new(err)
+                }
+            })?;
+            if chunk.is_none() {
+                self.decoder = None;
+            }
+            if !decoded.is_empty() {
+                return Ok(Async::Ready(Some(decoded.into())));
+            }
+        }
+    }
+}
+
+enum Decoder {
+    Gzip(::flate2::write::GzDecoder<DecodeBuf>),
+    Deflate(::flate2::write::ZlibDecoder<DecodeBuf>),
+    Brotli(::brotli::DecompressorWriter<DecodeBuf>),
+}
+
+impl Decoder {
+    fn new(encoding: Encoding, limit: Option<u64>) -> Decoder {
+        let buf = DecodeBuf {
+            buf: Vec::new(),
+            remaining: limit,
+        };
+        match encoding {
+            Encoding::Gzip => Decoder::Gzip(::flate2::write::GzDecoder::new(buf)),
+            Encoding::Deflate => Decoder::Deflate(::flate2::write::ZlibDecoder::new(buf)),
+            Encoding::Brotli => Decoder::Brotli(::brotli::DecompressorWriter::new(buf, 4096)),
+        }
+    }
+
+    // Decodes some input, returning what has been decoded so far.
+    fn write(&mut self, input: &[u8]) -> io::Result<Vec<u8>> {
+        match *self {
+            Decoder::Gzip(ref mut d) => d.write_all(input)?,
+            Decoder::Deflate(ref mut d) => d.write_all(input)?,
+            Decoder::Brotli(ref mut d) => d.write_all(input)?,
+        }
+        Ok(self.take())
+    }
+
+    // Decodes the rest of the input, failing if it was cut short.
+    fn finish(&mut self) -> io::Result<Vec<u8>> {
+        match *self {
+            Decoder::Gzip(ref mut d) => d.try_finish()?,
+            Decoder::Deflate(ref mut d) => d.try_finish()?,
+            Decoder::Brotli(ref mut d) => d.close()?,
+        }
+        Ok(self.take())
+    }
+
+    fn take(&mut self) -> Vec<u8> {
+        let buf = match *self {
+            Decoder::Gzip(ref mut d) => d.get_mut(),
+            Decoder::Deflate(ref mut d) => d.get_mut(),
+            Decoder::Brotli(ref mut d) => d.get_mut(),
+        };
+        mem::replace(&mut buf.buf, Vec::new())
+    }
+}
+
+// Collects decoded bytes, failing once there are more than allowed,
+// before a decompression bomb can use up all the memory.
+struct DecodeBuf {
+    buf: Vec<u8>,
+    remaining: Option<u64>,
+}
+
+impl Write for DecodeBuf {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        if let Some(ref mut remaining) = self.remaining {
+            let len = bytes.len() as u64;
+            if len > *remaining {
+                return Err(io::Error::new(io::ErrorKind::Other, PayloadTooLarge));
+            }
+            *remaining -= len;
+        }
+        self.buf.extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 /// An `impl Buf` representing a chunk in a request body.
 ///
 /// Yielded by a `BodyStream`.
@@ -327,7 +640,7 @@ impl fmt::Debug for StreamBuf {
 /// An error used in rejections when deserializing a request body fails.
 #[derive(Debug)]
 pub struct BodyDeserializeError {
-    cause: Box<StdError + Send + Sync>,
+    pub(crate) cause: Box<StdError + Send + Sync>,
 }
 
 impl fmt::Display for BodyDeserializeError {
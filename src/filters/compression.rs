@@ -0,0 +1,469 @@
+//! Compression Filters
+//!
+//! Wrappers that compress the bodies of replies, as negotiated with the
+//! `accept-encoding` of the request.
+
+use std::io::{self, Write};
+use std::mem;
+
+use brotli::CompressorWriter;
+use flate2::Compression as Level;
+use flate2::write::{GzEncoder, ZlibEncoder};
+use futures::{Async, Poll, Stream};
+use http::{HeaderMap, StatusCode};
+use http::header::{
+    HeaderValue, ACCEPT_ENCODING, ACCEPT_RANGES, CONTENT_ENCODING, CONTENT_LENGTH,
+    CONTENT_RANGE, CONTENT_TYPE, ETAG, VARY,
+};
+use hyper::{Body, Chunk};
+use hyper::body::Payload;
+
+use ::filter::{Filter, WrapSealed};
+use ::reject::Reject;
+use ::reply::{Reply, Response};
+
+use self::internal::WithCompression;
+
+/// Create a wrapping filter that compresses replies.
+///
+/// The encoding is negotiated from the request's `accept-encoding`,
+/// choosing among `br`, `gzip`, and `deflate` by their q-values. Bodies are
+/// compressed as they stream, so large bodies, like those of
+/// [`warp::fs`](::fs), aren't buffered.
+///
+/// Replies are left alone if they are smaller than the
+/// [`min_size`](Compression::min_size), already have a `content-encoding`,
+/// have a `content-range`, or have a `content-type` that is usually
+/// compressed already, such as images and archives.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let route = warp::fs::dir("static")
+///     .with(warp::compression::auto());
+/// ```
+pub fn auto() -> Compression {
+    Compression {
+        min_size: 1024,
+    }
+}
+
+/// Decorates a [`Filter`](::Filter) to compress replies.
+#[derive(Clone, Copy, Debug)]
+pub struct Compression {
+    min_size: u64,
+}
+
+impl Compression {
+    /// Skips compressing bodies smaller than this.
+    ///
+    /// The default is 1024 bytes. Streamed bodies without a
+    /// `content-length` are always compressed.
+    pub fn min_size(mut self, min_size: u64) -> Self {
+        self.min_size = min_size;
+        self
+    }
+}
+
+impl<F> WrapSealed<F> for Compression
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: Reject,
+{
+    type Wrapped = WithCompression<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithCompression {
+            filter,
+            compression: *self,
+        }
+    }
+}
+
+/// A content coding supported by warp.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Encoding {
+    Brotli,
+    Gzip,
+    Deflate,
+}
+
+impl Encoding {
+    // In order of preference, when equally acceptable.
+    const ALL: [Encoding; 3] = [Encoding::Brotli, Encoding::Gzip, Encoding::Deflate];
+
+    fn as_str(&self) -> &'static str {
+        match *self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+        }
+    }
+
+    // The `content-encoding` of a body, `None` if it isn't encoded, or `Err`
+    // if it isn't supported.
+    pub(crate) fn from_content_encoding(headers: &HeaderMap) -> Result<Option<Encoding>, ()> {
+        let value = match headers.get(CONTENT_ENCODING) {
+            Some(value) => value.to_str().map_err(|_| ())?.trim(),
+            None => return Ok(None),
+        };
+        if value.eq_ignore_ascii_case("x-gzip") {
+            return Ok(Some(Encoding::Gzip));
+        }
+        if value.eq_ignore_ascii_case("identity") || value.is_empty() {
+            return Ok(None);
+        }
+        Encoding::ALL
+            .iter()
+            .find(|encoding| value.eq_ignore_ascii_case(encoding.as_str()))
+            .map(|&encoding| Some(encoding))
+            .ok_or(())
+    }
+
+    // The most acceptable encoding by the `accept-encoding` of a request,
+    // or `None` if the body should be sent as is.
+    fn negotiate(headers: &HeaderMap) -> Option<Encoding> {
+        let mut any = None;
+        let mut qualities = [None; 3];
+        for value in headers.get_all(ACCEPT_ENCODING) {
+            let value = match value.to_str() {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+            for item in value.split(',') {
+                let mut parts = item.split(';');
+                let coding = parts.next().unwrap_or("").trim();
+                let q = parts
+                    .filter_map(|param| {
+                        let mut kv = param.splitn(2, '=');
+                        match (kv.next(), kv.next()) {
+                            (Some(k), Some(v)) if k.trim().eq_ignore_ascii_case("q") => {
+                                Some(v.trim().parse::<f32>().unwrap_or(0.0))
+                            },
+                            _ => None,
+                        }
+                    })
+                    .next()
+                    .unwrap_or(1.0);
+
+                if coding == "*" {
+                    any = Some(q);
+                } else if let Some(i) = Encoding::ALL
+                    .iter()
+                    .position(|encoding| coding.eq_ignore_ascii_case(encoding.as_str()))
+                {
+                    qualities[i] = Some(q);
+                } else if coding.eq_ignore_ascii_case("x-gzip") {
+                    qualities[1] = Some(q);
+                }
+            }
+        }
+
+        let mut best = None;
+        for (&encoding, q) in Encoding::ALL.iter().zip(qualities.iter()) {
+            let q = q.or(any).unwrap_or(0.0);
+            if q > 0.0 && best.map_or(true, |(_, best_q)| q > best_q) {
+                best = Some((encoding, q));
+            }
+        }
+        best.map(|(encoding, _)| encoding)
+    }
+}
+
+// Whether a response could be compressed, no matter the request.
+fn is_compressible(res: &Response, min_size: u64) -> bool {
+    if res.status() == StatusCode::NO_CONTENT ||
+        res.status() == StatusCode::NOT_MODIFIED ||
+        res.status() == StatusCode::PARTIAL_CONTENT {
+        return false;
+    }
+
+    let headers = res.headers();
+    if headers.contains_key(CONTENT_ENCODING) || headers.contains_key(CONTENT_RANGE) {
+        return false;
+    }
+
+    let len = headers
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())
+        .or_else(|| res.body().content_length());
+    if len.map_or(false, |len| len < min_size) {
+        return false;
+    }
+
+    let ct = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_ascii_lowercase());
+    match ct {
+        Some(ct) => !is_compressed_type(&ct),
+        None => true,
+    }
+}
+
+// Types that are compressed already, or that must be sent as they are
+// written, like event streams.
+fn is_compressed_type(ct: &str) -> bool {
+    if ct == "image/svg+xml" {
+        return false;
+    }
+    ct.starts_with("image/") ||
+        ct.starts_with("audio/") ||
+        ct.starts_with("video/") ||
+        ct.starts_with("font/woff") ||
+        ct == "text/event-stream" ||
+        ct == "application/zip" ||
+        ct == "application/gzip" ||
+        ct == "application/x-gzip" ||
+        ct == "application/x-bzip2" ||
+        ct == "application/x-xz" ||
+        ct == "application/x-7z-compressed" ||
+        ct == "application/x-rar-compressed" ||
+        ct == "application/zstd"
+}
+
+fn compress(mut res: Response, encoding: Option<Encoding>, min_size: u64) -> Response {
+    if !is_compressible(&res, min_size) {
+        return res;
+    }
+
+    add_vary(res.headers_mut());
+
+    let encoding = match encoding {
+        Some(encoding) => encoding,
+        None => return res,
+    };
+    trace!("compressing reply with {}", encoding.as_str());
+
+    {
+        let headers = res.headers_mut();
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static(encoding.as_str()));
+        // The compressed length isn't known until it has all been sent,
+        // and ranges of it wouldn't match ranges of the original body.
+        headers.remove(CONTENT_LENGTH);
+        headers.remove(ACCEPT_RANGES);
+        // A strong validator is only for a single representation.
+        let weak = headers
+            .get(ETAG)
+            .filter(|etag| !etag.as_bytes().starts_with(b"W/"))
+            .and_then(|etag| {
+                let mut weak = b"W/".to_vec();
+                weak.extend_from_slice(etag.as_bytes());
+                HeaderValue::from_bytes(&weak).ok()
+            });
+        if let Some(weak) = weak {
+            headers.insert(ETAG, weak);
+        }
+    }
+
+    let (parts, body) = res.into_parts();
+    let body = Body::wrap_stream(EncodeBody {
+        body,
+        encoder: Some(Encoder::new(encoding)),
+        pending: false,
+    });
+    Response::from_parts(parts, body)
+}
+
+fn add_vary(headers: &mut HeaderMap) {
+    let varies = headers
+        .get_all(VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|name| {
+            let name = name.trim();
+            name == "*" || name.eq_ignore_ascii_case("accept-encoding")
+        });
+    if !varies {
+        headers.append(VARY, HeaderValue::from_static("accept-encoding"));
+    }
+}
+
+// Compresses a body as it is sent.
+struct EncodeBody {
+    body: Body,
+    // Taken once the body is done.
+    encoder: Option<Encoder>,
+    // Whether some input hasn't been flushed out yet.
+    pending: bool,
+}
+
+impl Stream for EncodeBody {
+    type Item = Chunk;
+    type Error = Box<::std::error::Error + Send + Sync>;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        loop {
+            let encoder = match self.encoder {
+                Some(ref mut encoder) => encoder,
+                None => return Ok(Async::Ready(None)),
+            };
+            let encoded = match self.body.poll()? {
+                Async::Ready(Some(chunk)) => {
+                    self.pending = true;
+                    encoder.write(&chunk)?
+                },
+                Async::Ready(None) => {
+                    let encoded = self
+                        .encoder
+                        .take()
+                        .expect("encoder is set")
+                        .finish()?;
+                    if encoded.is_empty() {
+                        return Ok(Async::Ready(None));
+                    }
+                    encoded
+                },
+                Async::NotReady => {
+                    // Send what there is so far, instead of holding on to it
+                    // while waiting for more of the body.
+                    if !self.pending {
+                        return Ok(Async::NotReady);
+                    }
+                    self.pending = false;
+                    let encoded = encoder.flush()?;
+                    if encoded.is_empty() {
+                        return Ok(Async::NotReady);
+                    }
+                    encoded
+                },
+            };
+            if !encoded.is_empty() {
+                return Ok(Async::Ready(Some(encoded.into())));
+            }
+        }
+    }
+}
+
+enum Encoder {
+    Brotli(CompressorWriter<Vec<u8>>),
+    Gzip(GzEncoder<Vec<u8>>),
+    Deflate(ZlibEncoder<Vec<u8>>),
+}
+
+impl Encoder {
+    fn new(encoding: Encoding) -> Encoder {
+        match encoding {
+            // A low quality, since compressing is done on the fly.
+            Encoding::Brotli => Encoder::Brotli(CompressorWriter::new(Vec::new(), 4096, 4, 22)),
+            Encoding::Gzip => Encoder::Gzip(GzEncoder::new(Vec::new(), Level::default())),
+            Encoding::Deflate => Encoder::Deflate(ZlibEncoder::new(Vec::new(), Level::default())),
+        }
+    }
+
+    // Encodes some input, returning what has been encoded so far.
+    fn write(&mut self, input: &[u8]) -> io::Result<Vec<u8>> {
+        match *self {
+            Encoder::Brotli(ref mut e) => e.write_all(input)?,
+            Encoder::Gzip(ref mut e) => e.write_all(input)?,
+            Encoder::Deflate(ref mut e) => e.write_all(input)?,
+        }
+        Ok(self.take())
+    }
+
+    // Encodes all the input so far.
+    fn flush(&mut self) -> io::Result<Vec<u8>> {
+        match *self {
+            Encoder::Brotli(ref mut e) => e.flush()?,
+            Encoder::Gzip(ref mut e) => e.flush()?,
+            Encoder::Deflate(ref mut e) => e.flush()?,
+        }
+        Ok(self.take())
+    }
+
+    fn finish(self) -> io::Result<Vec<u8>> {
+        match self {
+            Encoder::Brotli(e) => Ok(e.into_inner()),
+            Encoder::Gzip(e) => e.finish(),
+            Encoder::Deflate(e) => e.finish(),
+        }
+    }
+
+    fn take(&mut self) -> Vec<u8> {
+        let buf = match *self {
+            Encoder::Brotli(ref mut e) => e.get_mut(),
+            Encoder::Gzip(ref mut e) => e.get_mut(),
+            Encoder::Deflate(ref mut e) => e.get_mut(),
+        };
+        mem::replace(buf, Vec::new())
+    }
+}
+
+mod internal {
+    use futures::{Async, Future, Poll};
+    use http::Method;
+
+    use ::filter::{FilterBase, Filter};
+    use ::reject::Reject;
+    use ::reply::{Reply, ReplySealed, Reply_};
+    use ::route;
+    use ::routes::Description;
+    use super::{compress, Compression, Encoding};
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone, Copy)]
+    pub struct WithCompression<F> {
+        pub(super) filter: F,
+        pub(super) compression: Compression,
+    }
+
+    impl<F> FilterBase for WithCompression<F>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: Reject,
+    {
+        type Extract = (Reply_,);
+        type Error = F::Error;
+        type Future = WithCompressionFuture<F::Future>;
+
+        fn filter(&self) -> Self::Future {
+            // The body of a response to `HEAD` isn't sent, so compressing
+            // it would only make its headers wrong.
+            let encoding = route::with(|route| {
+                if route.method() == Method::HEAD {
+                    None
+                } else {
+                    Encoding::negotiate(route.headers())
+                }
+            });
+            WithCompressionFuture {
+                future: self.filter.filter(),
+                encoding,
+                min_size: self.compression.min_size,
+            }
+        }
+
+        fn description(&self) -> Description {
+            self.filter.description()
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    pub struct WithCompressionFuture<F> {
+        future: F,
+        encoding: Option<Encoding>,
+        min_size: u64,
+    }
+
+    impl<F> Future for WithCompressionFuture<F>
+    where
+        F: Future,
+        F::Item: Reply,
+    {
+        type Item = (Reply_,);
+        type Error = F::Error;
+
+        fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+            let reply = try_ready!(self.future.poll());
+            let res = compress(reply.into_response(), self.encoding, self.min_size);
+            Ok(Async::Ready((Reply_(res),)))
+        }
+    }
+}
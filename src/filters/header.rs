@@ -10,8 +10,9 @@ use headers::{Header, HeaderMapExt};
 use http::HeaderMap;
 
 use ::never::Never;
-use ::filter::{Filter, filter_fn, filter_fn_one, One};
+use ::filter::{describe, Filter, filter_fn, filter_fn_one, One};
 use ::reject::{self, Rejection};
+use ::routes::Description;
 
 /// Create a `Filter` that tries to parse the specified header.
 ///
@@ -33,7 +34,7 @@ use ::reject::{self, Rejection};
 /// let foo = warp::header::<String>("foo");
 /// ```
 pub fn header<T: FromStr + Send>(name: &'static str) -> impl Filter<Extract=One<T>, Error=Rejection> + Copy {
-    filter_fn_one(move |route| {
+    describe(filter_fn_one(move |route| {
         trace!("header({:?})", name);
         route.headers()
             .get(name)
@@ -47,16 +48,22 @@ pub fn header<T: FromStr + Send>(name: &'static str) -> impl Filter<Extract=One<
                 T::from_str(s)
                     .map_err(|_| reject::known(InvalidHeader(name)))
             })
-    })
+    }), move || parsed(Description::Header { name: name.to_owned(), value: None }))
 }
 
 pub(crate) fn header2<T: Header + Send>() -> impl Filter<Extract=One<T>, Error=Rejection> + Copy {
-    filter_fn_one(move |route| {
+    describe(filter_fn_one(move |route| {
         trace!("header2({:?})", T::NAME);
         route.headers()
             .typed_get()
             .ok_or_else(|| reject::known(InvalidHeader(T::NAME.as_str())))
-    })
+    }), || parsed(Description::Header { name: T::NAME.as_str().to_owned(), value: None }))
+}
+
+// Parsing a header may reject it even when it's there, if only because
+// it isn't valid UTF-8.
+fn parsed(description: Description) -> Description {
+    Description::and(description, Description::Opaque)
 }
 
 /* TODO
@@ -90,7 +97,7 @@ where
 /// let must_dnt = warp::header::exact("dnt", "1");
 /// ```
 pub fn exact(name: &'static str, value: &'static str) -> impl Filter<Extract=(), Error=Rejection> + Copy {
-    filter_fn(move |route| {
+    describe(filter_fn(move |route| {
         trace!("exact?({:?}, {:?})", name, value);
         route.headers()
             .get(name)
@@ -102,7 +109,7 @@ pub fn exact(name: &'static str, value: &'static str) -> impl Filter<Extract=(),
                     Err(reject::known(InvalidHeader(name)))
                 }
             })
-    })
+    }), move || Description::Header { name: name.to_owned(), value: Some(value.to_owned()) })
 }
 
 /// Create a `Filter` that requires a header to match the value exactly.
@@ -117,7 +124,7 @@ pub fn exact(name: &'static str, value: &'static str) -> impl Filter<Extract=(),
 /// let keep_alive = warp::header::exact("connection", "keep-alive");
 /// ```
 pub fn exact_ignore_case(name: &'static str, value: &'static str) -> impl Filter<Extract=(), Error=Rejection> + Copy {
-    filter_fn(move |route| {
+    describe(filter_fn(move |route| {
         trace!("exact_ignore_case({:?}, {:?})", name, value);
         route.headers()
             .get(name)
@@ -129,6 +136,11 @@ pub fn exact_ignore_case(name: &'static str, value: &'static str) -> impl Filter
                     Err(reject::known(InvalidHeader(name)))
                 }
             })
+    }), move || {
+        // Other cases of the value match too, so it isn't the same as a
+        // `Header` with this exact value.
+        let header = Description::Header { name: name.to_owned(), value: None };
+        Description::and(header, Description::Opaque)
     })
 }
 
@@ -145,9 +157,9 @@ pub fn exact_ignore_case(name: &'static str, value: &'static str) -> impl Filter
 ///     });
 /// ```
 pub fn headers_cloned() -> impl Filter<Extract=One<HeaderMap>, Error=Never> + Copy {
-    filter_fn_one(|route| {
+    describe(filter_fn_one(|route| {
         Ok(route.headers().clone())
-    })
+    }), || Description::Any)
 }
 
 pub(crate) fn optional<T>()
@@ -155,9 +167,27 @@ pub(crate) fn optional<T>()
 where
     T: Header + Send,
 {
-    filter_fn_one(move |route| {
+    describe(filter_fn_one(move |route| {
         Ok(route.headers().typed_get())
-    })
+    }), || Description::Any)
+}
+
+// Like `header`, but extracts `None` if the header is missing.
+pub(crate) fn optional_value<T: FromStr + Send>(name: &'static str)
+    -> impl Filter<Extract=One<Option<T>>, Error=Rejection> + Copy
+{
+    describe(filter_fn_one(move |route| {
+        trace!("optional_value({:?})", name);
+        match route.headers().get(name) {
+            Some(value) => value
+                .to_str()
+                .ok()
+                .and_then(|s| T::from_str(s).ok())
+                .map(Some)
+                .ok_or_else(|| reject::known(InvalidHeader(name))),
+            None => Ok(None),
+        }
+    }), || parsed(Description::Any))
 }
 
 // ===== Rejections =====
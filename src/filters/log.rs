@@ -1,5 +1,6 @@
 //! Logger Filters
 
+use std::fmt;
 use std::marker::PhantomData;
 use std::time::Instant;
 
@@ -33,12 +34,12 @@ pub fn log(name: &'static str) -> Log<impl Fn(Info) + Copy> {
     let func = move |info: Info| {
         route::with(|route| {
             // TODO:
-            // - remote_addr
             // - response content length
             // - date
             info!(
                 target: name,
-                "\"{} {} {:?}\" {} {:?}",
+                "{} \"{} {} {:?}\" {} {:?}",
+                OptFmt(route.conn_info().remote_addr),
                 route.method(),
                 route.full_path(),
                 route.version(),
@@ -52,6 +53,18 @@ pub fn log(name: &'static str) -> Log<impl Fn(Info) + Copy> {
     }
 }
 
+struct OptFmt<T>(Option<T>);
+
+impl<T: fmt::Display> fmt::Display for OptFmt<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref t) = self.0 {
+            fmt::Display::fmt(t, f)
+        } else {
+            f.write_str("-")
+        }
+    }
+}
+
 // TODO:
 // pub fn custom(impl Fn(Info)) -> Log
 
@@ -97,6 +110,7 @@ mod internal {
     use ::filter::{FilterBase, Filter};
     use ::reject::Reject;
     use ::reply::{Reply, ReplySealed, Response};
+    use ::routes::Description;
     use super::{Info, Log};
 
     #[allow(missing_debug_implementations)]
@@ -136,6 +150,10 @@ mod internal {
             }
         }
 
+        fn description(&self) -> Description {
+            self.filter.description()
+        }
+
     }
 
     #[allow(missing_debug_implementations)]
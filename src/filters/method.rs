@@ -2,15 +2,23 @@
 //!
 //! The filters deal with the HTTP Method part of a request. Several here will
 //! match the request `Method`, and if not matched, will reject the request
-//! with a `405 Method Not Allowed`.
+//! with a `405 Method Not Allowed`. The methods of all the routes that
+//! matched the rest of the request are listed in its `Allow` header.
 //!
 //! There is also [`warp::method()`](method), which never rejects
 //! a request, and just extracts the method to be used in your filter chains.
+//!
+//! Finally, [`auto_head_options()`](auto_head_options) derives `HEAD` and
+//! `OPTIONS` responses from the other method filters.
 use http::Method;
 
-use ::filter::{And, Filter, filter_fn, filter_fn_one, One};
+use ::filter::{describe, And, Filter, filter_fn, filter_fn_one, One, WrapSealed};
 use ::never::Never;
-use ::reject::{CombineRejection, Rejection};
+use ::reject::{CombineRejection, Reject, Rejection};
+use ::reply::Reply;
+use ::routes::Description;
+
+use self::internal::WithAutoHeadOptions;
 
 pub use self::v2::{
     get as get2,
@@ -97,9 +105,70 @@ where
 ///     });
 /// ```
 pub fn method() -> impl Filter<Extract=One<Method>, Error=Never> + Copy {
-    filter_fn_one(|route| {
+    describe(filter_fn_one(|route| {
         Ok::<_, Never>(route.method().clone())
-    })
+    }), || Description::Any)
+}
+
+/// Create a wrapping filter that answers `HEAD` and `OPTIONS` requests on
+/// behalf of the routes it wraps.
+///
+/// - A `HEAD` request that is rejected with a `405 Method Not Allowed`, while
+///   a `GET` would have been allowed, is tried again as a `GET`. The reply's
+///   status and headers are used, but not its body.
+/// - An `OPTIONS` request that is rejected with a `405 Method Not Allowed` is
+///   answered with a `204 No Content`, with an `Allow` header listing the
+///   methods of the routes matching the path.
+///
+/// Routes explicitly handling `HEAD` or `OPTIONS`, such as with
+/// [`warp::head()`](v2::head) or [`warp::options()`](v2::options), still
+/// take precedence. Paths that don't match any route are still rejected.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let hello = warp::path("hello")
+///     .and(warp::get2())
+///     .map(|| "Hello, World!");
+/// let bye = warp::path("bye")
+///     .and(warp::post2())
+///     .map(|| "Good bye!");
+///
+/// // HEAD /hello and OPTIONS /hello and /bye work too.
+/// let routes = hello
+///     .or(bye)
+///     .with(warp::method::auto_head_options());
+/// ```
+pub fn auto_head_options() -> AutoHeadOptions {
+    AutoHeadOptions {
+        _priv: (),
+    }
+}
+
+/// Decorates a [`Filter`](::Filter) to answer `HEAD` and `OPTIONS` requests.
+///
+/// See [`auto_head_options`](auto_head_options).
+#[derive(Clone, Copy, Debug)]
+pub struct AutoHeadOptions {
+    _priv: (),
+}
+
+impl<F> WrapSealed<F> for AutoHeadOptions
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: Reject,
+    Rejection: From<F::Error>,
+{
+    type Wrapped = WithAutoHeadOptions<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithAutoHeadOptions {
+            filter,
+        }
+    }
 }
 
 // NOTE: This takes a static function instead of `&'static Method` directly
@@ -109,15 +178,15 @@ fn method_is<F>(func: F) -> impl Filter<Extract=(), Error=Rejection> + Copy
 where
     F: Fn() -> &'static Method + Copy,
 {
-    filter_fn(move |route| {
+    describe(filter_fn(move |route| {
         let method = func();
         trace!("method::{:?}?: {:?}", method, route.method());
         if route.method() == method {
             Ok(())
         } else {
-            Err(::reject::method_not_allowed())
+            Err(::reject::method_not_allowed(method.clone()))
         }
-    })
+    }), move || Description::Method(func().clone()))
 }
 
 pub mod v2 {
@@ -224,3 +293,165 @@ pub mod v2 {
         method_is(|| &Method::PATCH)
     }
 }
+
+mod internal {
+    use std::mem;
+
+    use futures::{Async, Future, Poll};
+    use http::{header, Method, StatusCode};
+    use http::header::HeaderValue;
+    use hyper::Body;
+    use hyper::body::Payload;
+
+    use ::filter::{FilterBase, Filter};
+    use ::reject::{allow_header, Reject, Rejection};
+    use ::reply::{Reply, ReplySealed, Response};
+    use ::route::{self, Snapshot};
+    use ::routes::Description;
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone, Copy)]
+    pub struct WithAutoHeadOptions<F> {
+        pub(super) filter: F,
+    }
+
+    impl<F> FilterBase for WithAutoHeadOptions<F>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: Reject,
+        Rejection: From<F::Error>,
+    {
+        type Extract = (Response,);
+        type Error = Rejection;
+        type Future = WithAutoHeadOptionsFuture<F>;
+
+        fn filter(&self) -> Self::Future {
+            let (method, original) = route::with(|route| {
+                (route.method().clone(), route.snapshot())
+            });
+            WithAutoHeadOptionsFuture {
+                method,
+                original,
+                state: State::First(self.filter.filter(), self.filter.clone()),
+            }
+        }
+
+        fn description(&self) -> Description {
+            self.filter.description()
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    pub struct WithAutoHeadOptionsFuture<F: Filter> {
+        method: Method,
+        original: Snapshot,
+        state: State<F>,
+    }
+
+    enum State<F: Filter> {
+        First(F::Future, F),
+        // Trying a HEAD request again, as a GET.
+        Get(F::Future),
+        Done,
+    }
+
+    impl<F: Filter> WithAutoHeadOptionsFuture<F> {
+        fn reset_route(&self, method: Option<Method>) {
+            let original = self.original;
+            route::with(|route| {
+                route.reset(original);
+                if let Some(method) = method {
+                    route.set_method(method);
+                }
+            });
+        }
+    }
+
+    impl<F> Future for WithAutoHeadOptionsFuture<F>
+    where
+        F: Filter,
+        F::Extract: Reply,
+        Rejection: From<F::Error>,
+    {
+        type Item = (Response,);
+        type Error = Rejection;
+
+        fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+            let rejection = match self.state {
+                State::First(ref mut first, _) => match first.poll() {
+                    Ok(Async::Ready(reply)) => {
+                        self.state = State::Done;
+                        return Ok(Async::Ready((reply.into_response(),)));
+                    },
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    Err(err) => Rejection::from(err),
+                },
+                State::Get(ref mut get) => {
+                    let result = get.poll();
+                    return match result {
+                        Ok(Async::Ready(reply)) => {
+                            self.state = State::Done;
+                            self.reset_route(Some(Method::HEAD));
+                            Ok(Async::Ready((without_body(reply.into_response()),)))
+                        },
+                        Ok(Async::NotReady) => Ok(Async::NotReady),
+                        Err(err) => {
+                            self.state = State::Done;
+                            self.reset_route(Some(Method::HEAD));
+                            Err(Rejection::from(err))
+                        },
+                    };
+                },
+                State::Done => panic!("polled after complete"),
+            };
+
+            let filter = match mem::replace(&mut self.state, State::Done) {
+                State::First(_, filter) => filter,
+                _ => unreachable!(),
+            };
+
+            if rejection.status() != StatusCode::METHOD_NOT_ALLOWED {
+                return Err(rejection);
+            }
+
+            let mut allowed = rejection.allowed_methods();
+            if self.method == Method::HEAD && allowed.contains(&Method::GET) {
+                trace!("auto HEAD, trying as GET");
+                self.reset_route(Some(Method::GET));
+                self.state = State::Get(filter.filter());
+                return self.poll();
+            }
+
+            if self.method == Method::OPTIONS {
+                if allowed.contains(&Method::GET) && !allowed.contains(&Method::HEAD) {
+                    allowed.push(Method::HEAD);
+                }
+                if !allowed.contains(&Method::OPTIONS) {
+                    allowed.push(Method::OPTIONS);
+                }
+                trace!("auto OPTIONS, allowed: {:?}", allowed);
+                let mut res = Response::default();
+                *res.status_mut() = StatusCode::NO_CONTENT;
+                if let Some(value) = allow_header(&allowed) {
+                    res.headers_mut().insert(header::ALLOW, value);
+                }
+                return Ok(Async::Ready((res,)));
+            }
+
+            Err(rejection)
+        }
+    }
+
+    // Keeps the headers of a response to a GET, but not its body. The length
+    // the body would have had is kept, if known.
+    fn without_body(res: Response) -> Response {
+        let (mut parts, body) = res.into_parts();
+        if !parts.headers.contains_key(header::CONTENT_LENGTH) {
+            if let Some(len) = body.content_length() {
+                parts.headers.insert(header::CONTENT_LENGTH, HeaderValue::from(len));
+            }
+        }
+        Response::from_parts(parts, Body::empty())
+    }
+}
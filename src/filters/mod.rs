@@ -3,17 +3,25 @@
 //! This module mostly serves as documentation to group together the list of
 //! built-in filters. Most of these are available at more convenient paths.
 
+pub mod addr;
 pub mod any;
 pub mod body;
+pub mod compression;
 pub mod cookie;
 pub mod ext;
 pub mod fs;
 pub mod header;
 pub mod log;
 pub mod method;
+pub mod multipart;
 pub mod path;
 pub mod query;
+pub mod reload;
 pub mod reply;
+pub mod router;
+pub mod sse;
+#[cfg(unix)]
+pub mod unix;
 pub mod ws;
 
 pub use ::filter::BoxedFilter;
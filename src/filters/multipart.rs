@@ -0,0 +1,931 @@
+//! Multipart body filters
+//!
+//! Filters that extract a `multipart/form-data` body for a route, usually
+//! from [`warp::body::multipart`](../body/fn.multipart.html).
+//!
+//! The body is parsed as it streams in, so the parts of a form are read
+//! one at a time, without buffering the whole upload in memory.
+
+use std::collections::hash_map::RandomState;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bytes::{Bytes, BytesMut};
+use futures::{future, Async, Future, Poll, Stream};
+use http::HeaderMap;
+use http::header::{HeaderName, HeaderValue, CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::Body;
+use mime;
+use tokio::fs::{File as TkFile, OpenOptions};
+use tokio::io as tk_io;
+use urlencoding;
+
+use ::body::{self, BodyDeserializeError};
+use ::filter::FilterBase;
+use ::reject::{self, Rejection};
+use ::route;
+
+// The most bytes of headers a single part may have.
+const MAX_HEADERS_SIZE: usize = 8 * 1024;
+
+/// Create a `Filter` that extracts a `multipart/form-data` body as a
+/// [`FormData`](FormData) stream of parts.
+///
+/// Rejects with `415 Unsupported Media Type` if the request isn't
+/// `multipart/form-data`.
+///
+/// # Warning
+///
+/// This does not have a default size limit, it would be wise to set one
+/// with [`max_length`](FormOptions::max_length) and
+/// [`max_part_size`](FormOptions::max_part_size).
+pub fn form() -> FormOptions {
+    FormOptions {
+        max_length: None,
+        max_part_size: None,
+    }
+}
+
+/// A `Filter` extracting a `multipart/form-data` body, created with
+/// [`form`](form).
+#[derive(Clone, Copy, Debug)]
+pub struct FormOptions {
+    max_length: Option<u64>,
+    max_part_size: Option<u64>,
+}
+
+impl FormOptions {
+    /// Limits the size of the whole body.
+    ///
+    /// Requests with a larger `content-length` are rejected upfront, while
+    /// streamed bodies fail with `413 Payload Too Large` once they pass it.
+    pub fn max_length(mut self, limit: u64) -> Self {
+        self.max_length = Some(limit);
+        self
+    }
+
+    /// Limits the size of the data of each part.
+    ///
+    /// Reading a part past the limit fails with `413 Payload Too Large`.
+    pub fn max_part_size(mut self, limit: u64) -> Self {
+        self.max_part_size = Some(limit);
+        self
+    }
+
+    fn start(&self, route: &mut route::Route) -> Result<FormData, Rejection> {
+        let boundary = boundary(route.headers())?;
+
+        if let Some(limit) = self.max_length {
+            let length = route
+                .headers()
+                .get(CONTENT_LENGTH)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u64>().ok());
+            if let Some(length) = length {
+                if length > limit {
+                    debug!("content-length: {} is over limit {}", length, limit);
+                    return Err(reject::payload_too_large());
+                }
+            }
+        }
+
+        let body = body::take_body(route)?;
+
+        // The delimiter is a CRLF and the boundary, so prefixing a CRLF
+        // lets the first boundary be found right at the start of the body.
+        let mut delimiter = BytesMut::with_capacity(boundary.len() + 4);
+        delimiter.extend_from_slice(b"\r\n--");
+        delimiter.extend_from_slice(boundary.as_bytes());
+
+        Ok(FormData {
+            inner: Arc::new(Mutex::new(Inner {
+                body,
+                buf: BytesMut::from(&b"\r\n"[..]),
+                delimiter: delimiter.freeze(),
+                state: State::Preamble,
+                part: 0,
+                part_len: 0,
+                total_len: 0,
+                max_length: self.max_length,
+                max_part_size: self.max_part_size,
+            })),
+        })
+    }
+}
+
+impl FilterBase for FormOptions {
+    type Extract = (FormData,);
+    type Error = Rejection;
+    type Future = future::FutureResult<(FormData,), Rejection>;
+
+    fn filter(&self) -> Self::Future {
+        future::result(route::with(|route| self.start(route)).map(|form| (form,)))
+    }
+}
+
+fn boundary(headers: &HeaderMap) -> Result<String, Rejection> {
+    let ct = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<mime::Mime>().ok());
+    let ct = match ct {
+        Some(ct) => ct,
+        None => {
+            debug!("multipart content-type missing or invalid");
+            return Err(reject::unsupported_media_type());
+        }
+    };
+
+    if ct.type_() != mime::MULTIPART || ct.subtype() != mime::FORM_DATA {
+        debug!("content-type {} isn't multipart/form-data", ct);
+        return Err(reject::unsupported_media_type());
+    }
+
+    match ct.get_param(mime::BOUNDARY) {
+        Some(boundary) if !boundary.as_str().is_empty() => Ok(boundary.as_str().to_owned()),
+        _ => Err(malformed("missing boundary")),
+    }
+}
+
+fn malformed(cause: &'static str) -> Rejection {
+    debug!("multipart body malformed: {}", cause);
+    reject::known(BodyDeserializeError {
+        cause: format!("multipart {}", cause).into(),
+    })
+}
+
+/// A `Stream` of the parts of a `multipart/form-data` body.
+///
+/// Extracted with the [`form`](form) filter. Each [`Part`](Part) must be
+/// read before polling for the next one, or the rest of its data is
+/// skipped.
+pub struct FormData {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl FormData {
+    /// Converts this into a `Stream` of parts whose data has been read.
+    ///
+    /// File parts, which have a `filename`, are written to temporary files
+    /// in `std::env::temp_dir()`. Other parts are kept in memory.
+    ///
+    /// This uses `tokio::fs`, and so must be run on the tokio threadpool,
+    /// as it is when served by warp.
+    pub fn spooled(self) -> impl Stream<Item=SpooledPart, Error=Rejection> + Send {
+        self.spooled_in(env::temp_dir())
+    }
+
+    /// Like [`spooled`](FormData::spooled), but writing the temporary files
+    /// in a specific directory.
+    pub fn spooled_in<P>(self, dir: P) -> impl Stream<Item=SpooledPart, Error=Rejection> + Send
+    where
+        P: Into<PathBuf>,
+    {
+        let dir = dir.into();
+        self.and_then(move |part| spool(part, &dir))
+    }
+}
+
+impl Stream for FormData {
+    type Item = Part;
+    type Error = Rejection;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let mut inner = lock(&self.inner);
+        let head = try_ready!(inner.poll_head());
+        Ok(Async::Ready(head.map(|head| Part {
+            head,
+            index: inner.part,
+            inner: self.inner.clone(),
+        })))
+    }
+}
+
+impl fmt::Debug for FormData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FormData")
+            .finish()
+    }
+}
+
+/// A single part of a `multipart/form-data` body.
+///
+/// This is a `Stream` of the chunks of its data.
+pub struct Part {
+    head: Head,
+    index: usize,
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Clone, Debug)]
+struct Head {
+    name: String,
+    filename: Option<String>,
+    content_type: Option<String>,
+    headers: HeaderMap,
+}
+
+impl Head {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn filename(&self) -> Option<&str> {
+        self.filename.as_ref().map(|s| s.as_str())
+    }
+
+    fn content_type(&self) -> Option<&str> {
+        self.content_type.as_ref().map(|s| s.as_str())
+    }
+}
+
+impl Part {
+    /// The name of the form field.
+    pub fn name(&self) -> &str {
+        self.head.name()
+    }
+
+    /// The filename of an uploaded file, if this part is one.
+    pub fn filename(&self) -> Option<&str> {
+        self.head.filename()
+    }
+
+    /// The `content-type` of this part, if it has one.
+    pub fn content_type(&self) -> Option<&str> {
+        self.head.content_type()
+    }
+
+    /// All the headers of this part.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.head.headers
+    }
+}
+
+impl Stream for Part {
+    type Item = Bytes;
+    type Error = Rejection;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        lock(&self.inner).poll_data(self.index)
+    }
+}
+
+impl fmt::Debug for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Part")
+            .field("name", &self.head.name)
+            .field("filename", &self.head.filename)
+            .field("content_type", &self.head.content_type)
+            .finish()
+    }
+}
+
+// ===== Parsing =====
+
+fn lock(inner: &Arc<Mutex<Inner>>) -> MutexGuard<Inner> {
+    inner.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+struct Inner {
+    body: Body,
+    buf: BytesMut,
+    delimiter: Bytes,
+    state: State,
+    // The index of the current part.
+    part: usize,
+    part_len: u64,
+    total_len: u64,
+    max_length: Option<u64>,
+    max_part_size: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum State {
+    // Before the first delimiter.
+    Preamble,
+    // Right after a delimiter, which either starts a part or ends the body.
+    Delimiter,
+    Headers,
+    Data,
+    Done,
+}
+
+impl Inner {
+    // Reads more of the body into the buffer, returning `false` at its end.
+    fn read(&mut self) -> Poll<bool, Rejection> {
+        let chunk = match self.body.poll() {
+            Ok(Async::Ready(chunk)) => chunk,
+            Ok(Async::NotReady) => return Ok(Async::NotReady),
+            Err(err) => {
+                debug!("multipart body read error: {}", err);
+                self.state = State::Done;
+                return Err(body::read_error(err));
+            },
+        };
+        match chunk {
+            Some(chunk) => {
+                self.total_len += chunk.len() as u64;
+                if let Some(limit) = self.max_length {
+                    if self.total_len > limit {
+                        debug!("multipart body is over limit {}", limit);
+                        self.state = State::Done;
+                        return Err(reject::payload_too_large());
+                    }
+                }
+                self.buf.extend_from_slice(&chunk);
+                Ok(Async::Ready(true))
+            },
+            None => Ok(Async::Ready(false)),
+        }
+    }
+
+    // Reads more of the body, failing if it has ended.
+    fn read_more(&mut self) -> Poll<(), Rejection> {
+        if try_ready!(self.read()) {
+            Ok(Async::Ready(()))
+        } else {
+            self.state = State::Done;
+            Err(malformed("body ended unexpectedly"))
+        }
+    }
+
+    fn poll_head(&mut self) -> Poll<Option<Head>, Rejection> {
+        loop {
+            match self.state {
+                State::Preamble => {
+                    match find(&self.buf, &self.delimiter) {
+                        Some(idx) => {
+                            self.buf.advance(idx + self.delimiter.len());
+                            self.state = State::Delimiter;
+                        },
+                        None => {
+                            // Keep only what could be the start of the delimiter.
+                            let keep = self.delimiter.len() - 1;
+                            if self.buf.len() > keep {
+                                let skip = self.buf.len() - keep;
+                                self.buf.advance(skip);
+                            }
+                            try_ready!(self.read_more());
+                        },
+                    }
+                },
+                State::Delimiter => {
+                    if self.buf.len() < 2 {
+                        try_ready!(self.read_more());
+                        continue;
+                    }
+                    if &self.buf[..2] == b"--" {
+                        trace!("multipart body end");
+                        self.state = State::Done;
+                    } else if &self.buf[..2] == b"\r\n" {
+                        self.buf.advance(2);
+                        self.state = State::Headers;
+                    } else {
+                        self.state = State::Done;
+                        return Err(malformed("boundary is invalid"));
+                    }
+                },
+                State::Headers => {
+                    let end = match find(&self.buf, b"\r\n\r\n") {
+                        Some(end) => end,
+                        // A part without headers.
+                        None if self.buf.starts_with(b"\r\n") => 0,
+                        None => {
+                            if self.buf.len() > MAX_HEADERS_SIZE {
+                                self.state = State::Done;
+                                return Err(malformed("part headers are too large"));
+                            }
+                            try_ready!(self.read_more());
+                            continue;
+                        },
+                    };
+                    let (head, skip) = if end == 0 {
+                        (parse_head(b""), 2)
+                    } else {
+                        (parse_head(&self.buf[..end]), end + 4)
+                    };
+                    self.buf.advance(skip);
+                    let head = head.map_err(|cause| {
+                        self.state = State::Done;
+                        malformed(cause)
+                    })?;
+                    trace!("multipart part {:?}", head.name);
+                    self.part += 1;
+                    self.part_len = 0;
+                    self.state = State::Data;
+                    return Ok(Async::Ready(Some(head)));
+                },
+                State::Data => {
+                    // Skip whatever is left of the previous part.
+                    let part = self.part;
+                    while try_ready!(self.poll_data(part)).is_some() {}
+                },
+                State::Done => return Ok(Async::Ready(None)),
+            }
+        }
+    }
+
+    fn poll_data(&mut self, part: usize) -> Poll<Option<Bytes>, Rejection> {
+        if part != self.part || self.state != State::Data {
+            return Ok(Async::Ready(None));
+        }
+
+        loop {
+            let data = match find(&self.buf, &self.delimiter) {
+                Some(0) => {
+                    self.buf.advance(self.delimiter.len());
+                    self.state = State::Delimiter;
+                    return Ok(Async::Ready(None));
+                },
+                Some(idx) => self.buf.split_to(idx),
+                None => {
+                    // The end of the buffer could be the start of the delimiter.
+                    let keep = self.delimiter.len() - 1;
+                    if self.buf.len() > keep {
+                        let len = self.buf.len() - keep;
+                        self.buf.split_to(len)
+                    } else {
+                        try_ready!(self.read_more());
+                        continue;
+                    }
+                },
+            };
+
+            self.part_len += data.len() as u64;
+            if let Some(limit) = self.max_part_size {
+                if self.part_len > limit {
+                    debug!("multipart part is over limit {}", limit);
+                    self.state = State::Done;
+                    return Err(reject::payload_too_large());
+                }
+            }
+            return Ok(Async::Ready(Some(data.freeze())));
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn parse_head(bytes: &[u8]) -> Result<Head, &'static str> {
+    let mut headers = HeaderMap::new();
+    if !bytes.is_empty() {
+        for line in bytes.split(|&b| b == b'\n') {
+            let line = if line.ends_with(b"\r") {
+                &line[..line.len() - 1]
+            } else {
+                line
+            };
+            let colon = line
+                .iter()
+                .position(|&b| b == b':')
+                .ok_or("part header is invalid")?;
+            let name = HeaderName::from_bytes(&line[..colon])
+                .map_err(|_| "part header name is invalid")?;
+            let value = HeaderValue::from_bytes(trim(&line[colon + 1..]))
+                .map_err(|_| "part header value is invalid")?;
+            headers.append(name, value);
+        }
+    }
+
+    let disposition = headers
+        .get(CONTENT_DISPOSITION)
+        .ok_or("part content-disposition missing")?
+        .to_str()
+        .map_err(|_| "part content-disposition is invalid")?;
+    let params = disposition_params(disposition)?;
+
+    let name = params
+        .iter()
+        .find(|&&(ref key, _)| key == "name")
+        .map(|&(_, ref value)| value.clone())
+        .ok_or("part name missing")?;
+    // An extended `filename*` is preferred over a plain `filename`.
+    let filename = params
+        .iter()
+        .find(|&&(ref key, _)| key == "filename*")
+        .and_then(|&(_, ref value)| decode_ext_value(value))
+        .or_else(|| {
+            params
+                .iter()
+                .find(|&&(ref key, _)| key == "filename")
+                .map(|&(_, ref value)| value.clone())
+        });
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_owned());
+
+    Ok(Head {
+        name,
+        filename,
+        content_type,
+        headers,
+    })
+}
+
+fn trim(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(bytes.len());
+    let end = bytes
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map(|i| i + 1)
+        .unwrap_or(start);
+    &bytes[start..end]
+}
+
+// Parses the parameters of a `content-disposition`, such as
+// `form-data; name="file"; filename="a.txt"`, with lowercased keys.
+fn disposition_params(value: &str) -> Result<Vec<(String, String)>, &'static str> {
+    let mut chars = value.chars().peekable();
+
+    // The disposition type itself.
+    while let Some(&c) = chars.peek() {
+        if c == ';' {
+            break;
+        }
+        chars.next();
+    }
+
+    let mut params = Vec::new();
+    while chars.next() == Some(';') {
+        let mut key = String::new();
+        while let Some(&c) = chars.peek() {
+            if c == '=' || c == ';' {
+                break;
+            }
+            key.push(c);
+            chars.next();
+        }
+        let key = key.trim().to_ascii_lowercase();
+
+        let mut value = String::new();
+        if chars.peek() == Some(&'=') {
+            chars.next();
+            while chars.peek() == Some(&' ') {
+                chars.next();
+            }
+            if chars.peek() == Some(&'"') {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c) => value.push(c),
+                            None => return Err("part content-disposition is invalid"),
+                        },
+                        Some(c) => value.push(c),
+                        None => return Err("part content-disposition is invalid"),
+                    }
+                }
+                while let Some(&c) = chars.peek() {
+                    if c == ';' {
+                        break;
+                    }
+                    chars.next();
+                }
+            } else {
+                while let Some(&c) = chars.peek() {
+                    if c == ';' {
+                        break;
+                    }
+                    value.push(c);
+                    chars.next();
+                }
+                let trimmed = value.trim().len();
+                value.truncate(trimmed);
+            }
+        }
+
+        if !key.is_empty() {
+            params.push((key, value));
+        }
+    }
+    Ok(params)
+}
+
+// Decodes an RFC 5987 value, like `UTF-8''na%C3%AFve.txt`.
+fn decode_ext_value(value: &str) -> Option<String> {
+    let mut pieces = value.splitn(3, '\'');
+    let charset = pieces.next()?;
+    let _language = pieces.next()?;
+    let encoded = pieces.next()?;
+    if !charset.eq_ignore_ascii_case("utf-8") {
+        return None;
+    }
+    urlencoding::decode(encoded).ok()
+}
+
+// ===== Spooling =====
+
+/// A part of a `multipart/form-data` body, with its data read.
+///
+/// Yielded by [`FormData::spooled`](FormData::spooled).
+#[derive(Debug)]
+pub struct SpooledPart {
+    head: Head,
+    data: PartData,
+}
+
+impl SpooledPart {
+    /// The name of the form field.
+    pub fn name(&self) -> &str {
+        self.head.name()
+    }
+
+    /// The filename of an uploaded file, if this part is one.
+    pub fn filename(&self) -> Option<&str> {
+        self.head.filename()
+    }
+
+    /// The `content-type` of this part, if it has one.
+    pub fn content_type(&self) -> Option<&str> {
+        self.head.content_type()
+    }
+
+    /// All the headers of this part.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.head.headers
+    }
+
+    /// The data of this part.
+    pub fn data(&self) -> &PartData {
+        &self.data
+    }
+
+    /// Converts this into the data of this part.
+    pub fn into_data(self) -> PartData {
+        self.data
+    }
+}
+
+/// The data of a [`SpooledPart`](SpooledPart).
+#[derive(Debug)]
+pub enum PartData {
+    /// The data of a field, kept in memory.
+    Bytes(Bytes),
+    /// The data of an uploaded file, written to a temporary file.
+    File(TempFile),
+}
+
+/// A temporary file holding an uploaded file.
+///
+/// The file is deleted when this is dropped, unless it is
+/// [`persist`](TempFile::persist)ed or [`keep`](TempFile::keep)t.
+#[derive(Debug)]
+pub struct TempFile {
+    path: PathBuf,
+    len: u64,
+}
+
+impl TempFile {
+    /// The path of the temporary file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The number of bytes written to the file.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether the file is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Moves the file to `path`, so it is no longer deleted.
+    ///
+    /// The file is renamed, so `path` should be on the same filesystem.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be renamed, the error gives the `TempFile` back,
+    /// so it can still be copied elsewhere instead.
+    pub fn persist<P: AsRef<Path>>(self, path: P) -> Result<(), PersistError> {
+        match fs::rename(&self.path, path) {
+            Ok(()) => {
+                self.keep();
+                Ok(())
+            },
+            Err(error) => Err(PersistError {
+                error,
+                file: self,
+            }),
+        }
+    }
+
+    /// Keeps the file where it is, returning its path.
+    pub fn keep(self) -> PathBuf {
+        let path = self.path.clone();
+        mem::forget(self);
+        path
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_file(&self.path) {
+            debug!("failed to remove temp file {:?}: {}", self.path, err);
+        }
+    }
+}
+
+/// An error moving a [`TempFile`](TempFile) with
+/// [`persist`](TempFile::persist), holding the file that wasn't moved.
+#[derive(Debug)]
+pub struct PersistError {
+    error: io::Error,
+    file: TempFile,
+}
+
+impl PersistError {
+    /// The error renaming the file.
+    pub fn error(&self) -> &io::Error {
+        &self.error
+    }
+
+    /// Takes back the file, which is still deleted once dropped.
+    pub fn into_file(self) -> TempFile {
+        self.file
+    }
+}
+
+impl fmt::Display for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "failed to persist temp file {:?}: {}", self.file.path, self.error)
+    }
+}
+
+impl ::std::error::Error for PersistError {
+    fn description(&self) -> &str {
+        "failed to persist temp file"
+    }
+}
+
+static NEXT_TEMP_FILE: AtomicUsize = AtomicUsize::new(0);
+
+fn spool(part: Part, dir: &Path) -> Box<Future<Item=SpooledPart, Error=Rejection> + Send> {
+    let head = part.head.clone();
+
+    if head.filename.is_none() {
+        return Box::new(part
+            .fold(BytesMut::new(), |mut buf, chunk| {
+                buf.extend_from_slice(&chunk);
+                Ok::<_, Rejection>(buf)
+            })
+            .map(move |buf| SpooledPart {
+                head,
+                data: PartData::Bytes(buf.freeze()),
+            }));
+    }
+
+    let path = dir.join(temp_name());
+    trace!("spooling part {:?} to {:?}", head.name, path);
+
+    // Only the owner may read the upload, and `create_new` refuses to
+    // follow anything already at the path.
+    let mut opts = fs::OpenOptions::new();
+    opts.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+    }
+
+    let fut = OpenOptions::from(opts)
+        .open(path.clone())
+        .map_err(spool_error)
+        .and_then(move |file| {
+            // From here on, the file is removed if anything fails.
+            let temp = TempFile {
+                path,
+                len: 0,
+            };
+            part.fold((file, temp), |(file, mut temp), chunk| {
+                temp.len += chunk.len() as u64;
+                tk_io::write_all(file, chunk)
+                    .map(move |(file, _)| (file, temp))
+                    .map_err(spool_error)
+            })
+        })
+        .and_then(|(file, temp): (TkFile, TempFile)| {
+            tk_io::flush(file)
+                .map(move |_| temp)
+                .map_err(spool_error)
+        })
+        .map(move |temp| SpooledPart {
+            head,
+            data: PartData::File(temp),
+        });
+    Box::new(fut)
+}
+
+// A name other users of the temp directory can't guess ahead of time.
+fn temp_name() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    process::id().hash(&mut hasher);
+    NEXT_TEMP_FILE.fetch_add(1, Ordering::Relaxed).hash(&mut hasher);
+    if let Ok(now) = SystemTime::now().duration_since(UNIX_EPOCH) {
+        now.hash(&mut hasher);
+    }
+    let random = hasher.finish();
+    // A second, independently keyed hash doubles the random bits.
+    let mut hasher = RandomState::new().build_hasher();
+    random.hash(&mut hasher);
+    format!("warp-upload-{:016x}{:016x}", random, hasher.finish())
+}
+
+fn spool_error(err: io::Error) -> Rejection {
+    debug!("multipart spool error: {}", err);
+    reject::known(SpoolError(err))
+}
+
+#[derive(Debug)]
+pub(crate) struct SpoolError(io::Error);
+
+impl fmt::Display for SpoolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Multipart spool error: {}", self.0)
+    }
+}
+
+impl ::std::error::Error for SpoolError {
+    fn description(&self) -> &str {
+        "Multipart spool error"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+
+    use super::*;
+
+    fn form_data(chunks: Vec<&'static [u8]>) -> FormData {
+        let body = Body::wrap_stream(stream::iter_ok::<_, io::Error>(chunks));
+        FormData {
+            inner: Arc::new(Mutex::new(Inner {
+                body,
+                buf: BytesMut::from(&b"\r\n"[..]),
+                delimiter: Bytes::from(&b"\r\n--b"[..]),
+                state: State::Preamble,
+                part: 0,
+                part_len: 0,
+                total_len: 0,
+                max_length: None,
+                max_part_size: None,
+            })),
+        }
+    }
+
+    #[test]
+    fn split_across_chunks() {
+        let body = b"--b\r\ncontent-disposition: form-data; name=a\r\n\r\nx\r\n-y\r\n--b\r\n\
+            content-disposition: form-data; name=\"b\"\r\n\r\nz\r\n--b--";
+        // Every possible chunk boundary, by sending a byte at a time.
+        let chunks = body.chunks(1).collect();
+
+        let parts = form_data(chunks)
+            .and_then(|part| {
+                let name = part.name().to_owned();
+                part.concat2().map(move |data| (name, data))
+            })
+            .collect()
+            .wait()
+            .expect("form data");
+        assert_eq!(parts, vec![
+            ("a".to_owned(), Bytes::from(&b"x\r\n-y"[..])),
+            ("b".to_owned(), Bytes::from(&b"z"[..])),
+        ]);
+    }
+
+    #[test]
+    fn disposition() {
+        assert_eq!(
+            disposition_params("form-data; name=\"a \\\"b\\\"\"; FILENAME = c.txt ").unwrap(),
+            vec![
+                ("name".to_owned(), "a \"b\"".to_owned()),
+                ("filename".to_owned(), "c.txt".to_owned()),
+            ]
+        );
+        assert!(disposition_params("form-data; name=\"a").is_err());
+        assert_eq!(decode_ext_value("utf-8'en'%E2%82%AC.txt"), Some("€.txt".to_owned()));
+        assert_eq!(decode_ext_value("iso-8859-1''a.txt"), None);
+    }
+}
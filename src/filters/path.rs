@@ -5,7 +5,17 @@
 //! - [`path`](./fn.path.html) matches a specific segment, like `/foo`.
 //! - [`param`](./fn.param.html) tries to parse a segment into a type, like `/:u16`.
 //! - [`end`](./fn.end.html) matches when the path end is found.
+//! - [`end_with`](./fn.end_with.html) matches the path end, with a
+//!   [`TrailingSlash`](./enum.TrailingSlash.html) policy.
 //! - [`path!`](../../macro.path.html) eases combining multiple `path` and `param` filters.
+//! - [`template`](./fn.template.html) extracts named parameters into a struct.
+//! - [`normalize`](./fn.normalize.html) cleans up the path before routing.
+//!
+//! # Percent-encoding
+//!
+//! Segments are percent-decoded before being compared by `path`, or parsed
+//! by `param`, so `/users/J%C3%BCrgen` extracts `"Jürgen"`. Use
+//! [`param_raw`](./fn.param_raw.html) to get the segment as it was sent.
 //!
 //! # Routing
 //!
@@ -126,20 +136,29 @@
 //!     .or(math);
 //! ```
 
+use std::borrow::Cow;
+use std::error::Error as StdError;
 use std::fmt;
+use std::marker::PhantomData;
 use std::str::FromStr;
+use std::sync::Arc;
 
+use futures::future;
 use http::uri::PathAndQuery;
+use serde::de::DeserializeOwned;
+use urlencoding;
 
-use ::filter::{Filter, filter_fn, Tuple, One, one};
+use ::filter::{describe, Filter, FilterBase, filter_fn, Tuple, One, one};
 use ::never::Never;
 use ::reject::{self, Rejection};
-use ::route::Route;
+use ::route::{self, Route};
+use ::routes::Description;
 
 
 /// Create an exact match path segment `Filter`.
 ///
-/// This will try to match exactly to the current request path segment.
+/// This will try to match exactly to the current request path segment, once
+/// percent-decoded.
 ///
 /// # Panics
 ///
@@ -158,14 +177,14 @@ pub fn path(p: &'static str) -> impl Filter<Extract=(), Error=Rejection> + Copy
     assert!(!p.is_empty(), "exact path segments should not be empty");
     assert!(!p.contains('/'), "exact path segments should not contain a slash: {:?}", p);
 
-    segment(move |seg| {
+    describe(segment(move |seg| {
         trace!("{:?}?: {:?}", p, seg);
-        if seg == p {
+        if seg == p || (seg.contains('%') && decode(seg).ok().map_or(false, |seg| seg == p)) {
             Ok(())
         } else {
             Err(reject::not_found())
         }
-    })
+    }), move || Description::Path(p.to_owned()))
 }
 
 #[doc(hidden)]
@@ -186,22 +205,84 @@ pub fn index() -> impl Filter<Extract=(), Error=Rejection> + Copy {
 ///     .map(|| "Hello, World!");
 /// ```
 pub fn end() -> impl Filter<Extract=(), Error=Rejection> + Copy {
-    filter_fn(move |route| {
+    describe(filter_fn(move |route| {
         if route.path().is_empty() {
             Ok(())
         } else {
             Err(reject::not_found())
         }
-    })
+    }), || Description::End)
+}
+
+/// Matches the end of a route, handling a trailing slash with `policy`.
+///
+/// [`end`](./fn.end.html) is the same as
+/// `end_with(TrailingSlash::Lenient)`.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::path::TrailingSlash;
+///
+/// // Matches `/about`, and redirects `/about/` to it.
+/// let about = warp::path("about")
+///     .and(warp::path::end_with(TrailingSlash::Redirect))
+///     .map(|| "About us");
+/// ```
+pub fn end_with(policy: TrailingSlash) -> impl Filter<Extract=(), Error=Rejection> + Copy {
+    describe(filter_fn(move |route| {
+        if !route.path().is_empty() {
+            return Err(reject::not_found());
+        }
+
+        let full = route.full_path();
+        if full.len() == 1 || !full.ends_with('/') {
+            return Ok(());
+        }
+
+        match policy {
+            TrailingSlash::Lenient => Ok(()),
+            TrailingSlash::Strict => Err(reject::not_found()),
+            TrailingSlash::Redirect => {
+                let mut location = full.trim_right_matches('/').to_owned();
+                if location.is_empty() {
+                    location.push('/');
+                }
+                if let Some(query) = route.query() {
+                    location.push('?');
+                    location.push_str(query);
+                }
+                trace!("redirecting {:?} to {:?}", full, location);
+                Err(reject::trailing_slash(location, route.method()))
+            },
+        }
+    }), || Description::End)
+}
+
+/// How [`end_with`](./fn.end_with.html) treats a request path with a
+/// trailing slash, like `/about/`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrailingSlash {
+    /// Rejects the request with `404 Not Found`.
+    Strict,
+    /// Matches, as if there was no trailing slash.
+    Lenient,
+    /// Rejects the request with a redirect to the path without the
+    /// trailing slash. This is a `301 Moved Permanently` for `GET` and
+    /// `HEAD` requests, and a `308 Permanent Redirect` otherwise, so the
+    /// method and body are kept.
+    Redirect,
 }
 
 /// Extract a parameter from a path segment.
 ///
 /// This will try to parse a value from the current request path
-/// segment, and if successful, the value is returned as the `Filter`'s
-/// "extracted" value.
+/// segment, once percent-decoded, and if successful, the value is returned
+/// as the `Filter`'s "extracted" value.
 ///
-/// If the value could not be parsed, rejects with a `404 Not Found`.
+/// If the value could not be decoded or parsed, rejects with a
+/// `404 Not Found`.
 ///
 /// # Example
 ///
@@ -213,16 +294,44 @@ pub fn end() -> impl Filter<Extract=(), Error=Rejection> + Copy {
 ///         format!("You asked for /{}", id)
 ///     });
 /// ```
-pub fn param<T: FromStr + Send>() -> impl Filter<Extract=One<T>, Error=Rejection> + Copy {
-    segment(|seg| {
+pub fn param<T: FromStr + Send + 'static>() -> impl Filter<Extract=One<T>, Error=Rejection> + Copy {
+    describe(segment(|seg| {
         trace!("param?: {:?}", seg);
         if seg.is_empty() {
             return Err(reject::not_found());
         }
+        T::from_str(&decode(seg)?)
+            .map(one)
+            .map_err(|_| reject::not_found())
+    }), Description::param::<T>)
+}
+
+/// Extract a parameter from a path segment, without percent-decoding it.
+///
+/// This is like [`param`](./fn.param.html), but the segment is parsed
+/// exactly as it was sent.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// // GET /a%2Fb extracts "a%2Fb"
+/// let route = warp::path::param_raw()
+///     .map(|raw: String| {
+///         format!("You asked for /{}", raw)
+///     });
+/// ```
+pub fn param_raw<T: FromStr + Send + 'static>() -> impl Filter<Extract=One<T>, Error=Rejection> + Copy {
+    describe(segment(|seg| {
+        trace!("param_raw?: {:?}", seg);
+        if seg.is_empty() {
+            return Err(reject::not_found());
+        }
         T::from_str(seg)
             .map(one)
             .map_err(|_| reject::not_found())
-    })
+    }), Description::param::<T>)
 }
 
 /// Extract a parameter from a path segment.
@@ -246,21 +355,21 @@ pub fn param<T: FromStr + Send>() -> impl Filter<Extract=One<T>, Error=Rejection
 /// ```
 pub fn param2<T>() -> impl Filter<Extract=One<T>, Error=Rejection> + Copy
 where
-    T: FromStr + Send,
+    T: FromStr + Send + 'static,
     T::Err: Into<::reject::Cause>
 {
-    segment(|seg| {
+    describe(segment(|seg| {
         trace!("param?: {:?}", seg);
         if seg.is_empty() {
             return Err(reject::not_found());
         }
-        T::from_str(seg)
+        T::from_str(&decode(seg)?)
             .map(one)
             .map_err(|err| {
                 #[allow(deprecated)]
                 reject::not_found().with(err.into())
             })
-    })
+    }), Description::param::<T>)
 }
 
 /// Extract the unmatched tail of the path.
@@ -281,7 +390,7 @@ where
 ///     });
 /// ```
 pub fn tail() -> impl Filter<Extract=One<Tail>, Error=Never> + Copy {
-    filter_fn(move |route| {
+    describe(filter_fn(move |route| {
         let path = path_and_query(&route);
         let idx = route.matched_path_index();
 
@@ -294,7 +403,7 @@ pub fn tail() -> impl Filter<Extract=One<Tail>, Error=Never> + Copy {
             path,
             start_index: idx,
         }))
-    })
+    }), || Description::Tail)
 }
 
 /// Represents that tail part of a request path, returned by the `tail()` filter.
@@ -336,7 +445,7 @@ impl fmt::Debug for Tail {
 ///     });
 /// ```
 pub fn peek() -> impl Filter<Extract=One<Peek>, Error=Never> + Copy {
-    filter_fn(move |route| {
+    describe(filter_fn(move |route| {
         let path = path_and_query(&route);
         let idx = route.matched_path_index();
 
@@ -344,7 +453,7 @@ pub fn peek() -> impl Filter<Extract=One<Peek>, Error=Never> + Copy {
             path,
             start_index: idx,
         }))
-    })
+    }), || Description::Any)
 }
 
 /// Represents that tail part of a request path, returned by the `tail()` filter.
@@ -406,9 +515,9 @@ impl fmt::Debug for Peek {
 ///     });
 /// ```
 pub fn full() -> impl Filter<Extract=One<FullPath>, Error=Never> + Copy {
-    filter_fn(move |route| {
+    describe(filter_fn(move |route| {
         Ok(one(FullPath(path_and_query(&route))))
-    })
+    }), || Description::Any)
 }
 
 /// Represents the full request path, returned by the `full()` filter.
@@ -427,6 +536,611 @@ impl fmt::Debug for FullPath {
     }
 }
 
+/// Create a `Filter` that matches a path template, and extracts its named
+/// parameters into a `T`.
+///
+/// A template is a list of segments, starting with a `/`:
+///
+/// - A literal segment, like `orgs`, matches exactly that segment.
+/// - A parameter, like `{org}`, matches any non-empty segment.
+/// - A final `*` matches the rest of the path, which is left for later
+///   filters to match.
+///
+/// Otherwise, the template must match the whole path, as with
+/// [`end`](./fn.end.html). If the path doesn't match the template, the
+/// request is rejected with a `404 Not Found`.
+///
+/// The parameters are percent-decoded, and deserialized by name into a `T`,
+/// like [`query`](::query::query) does for query strings. A tuple, such as
+/// `(String, u32)`, is deserialized from the parameters in order instead.
+/// If a parameter is missing from the template, or cannot be deserialized,
+/// the request is rejected with a `400 Bad Request`, and an
+/// [`InvalidParam`](./struct.InvalidParam.html) cause naming it.
+///
+/// # Panics
+///
+/// This panics if the `template` is not valid.
+///
+/// # Example
+///
+/// ```
+/// #[macro_use] extern crate serde_derive;
+/// # extern crate warp;
+/// use warp::Filter;
+///
+/// #[derive(Deserialize)]
+/// struct Repo {
+///     org: String,
+///     repo: String,
+/// }
+///
+/// # fn main() {
+/// let route = warp::path::template("/orgs/{org}/repos/{repo}")
+///     .map(|r: Repo| {
+///         format!("You asked for {}/{}", r.org, r.repo)
+///     });
+/// # }
+/// ```
+pub fn template<T>(template: &str) -> impl Filter<Extract=One<T>, Error=Rejection> + Clone
+where
+    T: DeserializeOwned + Send,
+{
+    let mut tail = false;
+    let segments = parse_template(template)
+        .into_iter()
+        .filter_map(|seg| match seg {
+            Segment::Static(s) => Some(TemplateSegment::Static(s.to_owned())),
+            Segment::Param(name) => Some(TemplateSegment::Param(name.to_owned())),
+            Segment::Tail => {
+                tail = true;
+                None
+            },
+        })
+        .collect();
+
+    Template {
+        segments: Arc::new(segments),
+        tail,
+        _marker: PhantomData,
+    }
+}
+
+struct Template<T> {
+    segments: Arc<Vec<TemplateSegment>>,
+    tail: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+enum TemplateSegment {
+    Static(String),
+    Param(String),
+}
+
+impl<T> Clone for Template<T> {
+    fn clone(&self) -> Template<T> {
+        Template {
+            segments: self.segments.clone(),
+            tail: self.tail,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: DeserializeOwned + Send> FilterBase for Template<T> {
+    type Extract = One<T>;
+    type Error = Rejection;
+    type Future = future::FutureResult<One<T>, Rejection>;
+
+    fn filter(&self) -> Self::Future {
+        route::with(|route| {
+            let (value, end) = {
+                let path = route.path();
+                let mut params = Vec::new();
+                let mut start = 0;
+                let mut end = 0;
+                for template_seg in self.segments.iter() {
+                    if start >= path.len() {
+                        return Err(reject::not_found());
+                    }
+                    let seg = path[start..]
+                        .splitn(2, '/')
+                        .next()
+                        .expect("split always has at least 1");
+                    match *template_seg {
+                        TemplateSegment::Static(ref s) => {
+                            if *decode(seg)? != **s {
+                                return Err(reject::not_found());
+                            }
+                        },
+                        TemplateSegment::Param(ref name) => {
+                            if seg.is_empty() {
+                                return Err(reject::not_found());
+                            }
+                            params.push((name.as_str(), decode(seg)?));
+                        },
+                    }
+                    end = start + seg.len();
+                    start = end + 1;
+                }
+
+                if !self.tail && start < path.len() {
+                    return Err(reject::not_found());
+                }
+
+                trace!("template params: {:?}", params);
+                let value = de::from_params::<T>(&params)
+                    .map_err(|err| {
+                        debug!("invalid path parameter: {}", err);
+                        reject::known(err)
+                    })?;
+                (value, end)
+            };
+            if !self.segments.is_empty() {
+                route.set_unmatched_path(end);
+            }
+            Ok(one(value))
+        })
+        .into()
+    }
+
+    fn description(&self) -> Description {
+        let mut description = self.segments
+            .iter()
+            .map(|seg| match *seg {
+                TemplateSegment::Static(ref s) => Description::Path(s.clone()),
+                TemplateSegment::Param(ref name) => Description::Capture(name.clone()),
+            })
+            .fold(Description::Any, Description::and);
+        if !self.tail {
+            description = Description::and(description, Description::End);
+        }
+        // Deserializing the parameters may reject.
+        Description::and(description, Description::Opaque)
+    }
+}
+
+/// A path parameter extracted by [`template`](./fn.template.html) was
+/// missing or invalid.
+#[derive(Debug)]
+pub struct InvalidParam {
+    name: Option<String>,
+    message: String,
+}
+
+impl InvalidParam {
+    /// The name of the offending parameter, if known.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(String::as_str)
+    }
+}
+
+impl fmt::Display for InvalidParam {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name {
+            Some(ref name) => write!(f, "Invalid path parameter `{}`: {}", name, self.message),
+            None => write!(f, "Invalid path parameters: {}", self.message),
+        }
+    }
+}
+
+impl StdError for InvalidParam {
+    fn description(&self) -> &str {
+        "Invalid path parameter"
+    }
+}
+
+mod de {
+    use std::borrow::Cow;
+    use std::fmt;
+    use std::slice;
+
+    use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+
+    use super::InvalidParam;
+
+    pub(super) fn from_params<T: DeserializeOwned>(params: &[(&str, Cow<str>)]) -> Result<T, InvalidParam> {
+        T::deserialize(Params {
+            params,
+        })
+    }
+
+    impl de::Error for InvalidParam {
+        fn custom<T: fmt::Display>(msg: T) -> Self {
+            InvalidParam {
+                name: None,
+                message: msg.to_string(),
+            }
+        }
+
+        fn missing_field(field: &'static str) -> Self {
+            InvalidParam {
+                name: Some(field.to_owned()),
+                message: "missing".to_owned(),
+            }
+        }
+    }
+
+    struct Params<'a> {
+        params: &'a [(&'a str, Cow<'a, str>)],
+    }
+
+    impl<'de, 'a> de::Deserializer<'de> for Params<'a> {
+        type Error = InvalidParam;
+
+        fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            visitor.visit_map(ParamsAccess {
+                iter: self.params.iter(),
+                value: None,
+            })
+        }
+
+        fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            visitor.visit_seq(ParamsAccess {
+                iter: self.params.iter(),
+                value: None,
+            })
+        }
+
+        fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+            self.deserialize_seq(visitor)
+        }
+
+        fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+            self.deserialize_seq(visitor)
+        }
+
+        forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct map struct
+            enum identifier ignored_any
+        }
+    }
+
+    struct ParamsAccess<'a> {
+        iter: slice::Iter<'a, (&'a str, Cow<'a, str>)>,
+        value: Option<&'a (&'a str, Cow<'a, str>)>,
+    }
+
+    impl<'de, 'a> de::MapAccess<'de> for ParamsAccess<'a> {
+        type Error = InvalidParam;
+
+        fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+            match self.iter.next() {
+                Some(param) => {
+                    self.value = Some(param);
+                    seed.deserialize(param.0.into_deserializer()).map(Some)
+                },
+                None => Ok(None),
+            }
+        }
+
+        fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+            let &(name, ref value) = self.value
+                .take()
+                .expect("next_value_seed called before next_key_seed");
+            seed.deserialize(Value { name, value })
+                .map_err(|err| err.named(name))
+        }
+    }
+
+    impl<'de, 'a> de::SeqAccess<'de> for ParamsAccess<'a> {
+        type Error = InvalidParam;
+
+        fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+            match self.iter.next() {
+                Some(&(name, ref value)) => seed
+                    .deserialize(Value { name, value })
+                    .map(Some)
+                    .map_err(|err| err.named(name)),
+                None => Ok(None),
+            }
+        }
+    }
+
+    impl InvalidParam {
+        fn named(mut self, name: &str) -> InvalidParam {
+            if self.name.is_none() {
+                self.name = Some(name.to_owned());
+            }
+            self
+        }
+    }
+
+    struct Value<'a> {
+        name: &'a str,
+        value: &'a str,
+    }
+
+    macro_rules! parse_value {
+        ($($method:ident => $visit:ident,)*) => {
+            $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                match self.value.parse() {
+                    Ok(v) => visitor.$visit(v),
+                    Err(err) => Err(InvalidParam {
+                        name: Some(self.name.to_owned()),
+                        message: err.to_string(),
+                    }),
+                }
+            }
+            )*
+        };
+    }
+
+    impl<'de, 'a> de::Deserializer<'de> for Value<'a> {
+        type Error = InvalidParam;
+
+        fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            visitor.visit_str(self.value)
+        }
+
+        parse_value! {
+            deserialize_bool => visit_bool,
+            deserialize_i8 => visit_i8,
+            deserialize_i16 => visit_i16,
+            deserialize_i32 => visit_i32,
+            deserialize_i64 => visit_i64,
+            deserialize_u8 => visit_u8,
+            deserialize_u16 => visit_u16,
+            deserialize_u32 => visit_u32,
+            deserialize_u64 => visit_u64,
+            deserialize_f32 => visit_f32,
+            deserialize_f64 => visit_f64,
+            deserialize_char => visit_char,
+        }
+
+        fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            visitor.visit_some(self)
+        }
+
+        fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+            visitor.visit_newtype_struct(self)
+        }
+
+        fn deserialize_enum<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            _variants: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            visitor.visit_enum(self.value.into_deserializer())
+        }
+
+        forward_to_deserialize_any! {
+            str string bytes byte_buf unit unit_struct seq tuple tuple_struct
+            map struct identifier ignored_any
+        }
+    }
+}
+
+/// Create a `Filter` that normalizes the rest of the request path, before
+/// routing.
+///
+/// By default, this:
+///
+/// - canonicalizes percent-encoding, decoding unreserved characters like
+///   `%7E`, and upper-casing the rest like `%C3%BC`,
+/// - collapses duplicate slashes, so `//a///b` becomes `/a/b`,
+/// - resolves `.` and `..` segments, so `/a/./b/../c` becomes `/a/c`.
+///
+/// Each step can be turned off with the methods of
+/// [`Normalize`](./struct.Normalize.html). Only the part of the path not
+/// already matched by previous filters is changed, and `..` never goes
+/// above it. Later filters, including [`full`](./fn.full.html) and the
+/// [`log`](::log()) filter, see the normalized path.
+///
+/// This never rejects a request.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let hello = warp::path("hello")
+///     .and(warp::path::end())
+///     .map(|| "Hello, World!");
+///
+/// // Matches `/hello`, as well as `//hello` or `/foo/../hello`.
+/// let route = warp::path::normalize()
+///     .and(hello);
+/// ```
+pub fn normalize() -> Normalize {
+    Normalize {
+        dot_segments: true,
+        merge_slashes: true,
+        percent_encoding: true,
+    }
+}
+
+/// A `Filter` normalizing the request path, created with
+/// [`normalize`](./fn.normalize.html).
+#[derive(Clone, Copy, Debug)]
+pub struct Normalize {
+    dot_segments: bool,
+    merge_slashes: bool,
+    percent_encoding: bool,
+}
+
+impl Normalize {
+    /// Sets whether `.` and `..` segments are resolved.
+    ///
+    /// Default is `true`.
+    pub fn dot_segments(mut self, enabled: bool) -> Self {
+        self.dot_segments = enabled;
+        self
+    }
+
+    /// Sets whether duplicate slashes are collapsed into one.
+    ///
+    /// Default is `true`.
+    pub fn merge_slashes(mut self, enabled: bool) -> Self {
+        self.merge_slashes = enabled;
+        self
+    }
+
+    /// Sets whether percent-encoding is canonicalized.
+    ///
+    /// Default is `true`.
+    pub fn percent_encoding(mut self, enabled: bool) -> Self {
+        self.percent_encoding = enabled;
+        self
+    }
+
+    fn normalize(&self, path: &str) -> String {
+        let mut path = if self.percent_encoding {
+            canonical_percent_encoding(path)
+        } else {
+            path.to_owned()
+        };
+
+        if self.merge_slashes {
+            let mut merged = String::with_capacity(path.len());
+            for c in path.chars() {
+                // The matched part of the path always ends with a slash.
+                if c == '/' && (merged.is_empty() || merged.ends_with('/')) {
+                    continue;
+                }
+                merged.push(c);
+            }
+            path = merged;
+        }
+
+        if self.dot_segments {
+            let mut segments = Vec::new();
+            let mut trailing_slash = false;
+            for seg in path.split('/') {
+                trailing_slash = seg == "." || seg == "..";
+                match seg {
+                    "." => (),
+                    ".." => {
+                        segments.pop();
+                    },
+                    seg => segments.push(seg),
+                }
+            }
+            let mut resolved = segments.join("/");
+            if trailing_slash && !resolved.is_empty() {
+                resolved.push('/');
+            }
+            path = resolved;
+        }
+
+        path
+    }
+}
+
+impl FilterBase for Normalize {
+    type Extract = ();
+    type Error = Never;
+    type Future = future::FutureResult<(), Never>;
+
+    fn filter(&self) -> Self::Future {
+        route::with(|route| {
+            let normalized = self.normalize(route.path());
+            if normalized != route.path() {
+                trace!("normalized path {:?} to {:?}", route.path(), normalized);
+                route.replace_unmatched_path(&normalized);
+            }
+        });
+        future::ok(())
+    }
+
+    fn description(&self) -> Description {
+        Description::Any
+    }
+}
+
+// Decodes unreserved characters, and upper-cases the hex digits of the rest.
+// Any other byte that can't appear as it is, such as UTF-8, is encoded.
+fn canonical_percent_encoding(path: &str) -> String {
+    fn hex(b: u8) -> Option<u8> {
+        (b as char).to_digit(16).map(|d| d as u8)
+    }
+
+    fn push_encoded(out: &mut String, b: u8) {
+        const HEX: &[u8; 16] = b"0123456789ABCDEF";
+        out.push('%');
+        out.push(HEX[(b >> 4) as usize] as char);
+        out.push(HEX[(b & 0xF) as usize] as char);
+    }
+
+    let bytes = path.as_bytes();
+    let mut out = String::with_capacity(path.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let (Some(hi), Some(lo)) = (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                let b = hi << 4 | lo;
+                if b.is_ascii_alphanumeric() || b == b'-' || b == b'.' || b == b'_' || b == b'~' {
+                    out.push(b as char);
+                } else {
+                    push_encoded(&mut out, b);
+                }
+                i += 3;
+                continue;
+            }
+        }
+        let b = bytes[i];
+        if b > b' ' && b < 0x7F {
+            out.push(b as char);
+        } else {
+            push_encoded(&mut out, b);
+        }
+        i += 1;
+    }
+    out
+}
+
+/// A segment of a path template, used by [`template`](./fn.template.html)
+/// and the [`Router`](::router::Router).
+pub(crate) enum Segment<'a> {
+    Static(&'a str),
+    Param(&'a str),
+    Tail,
+}
+
+pub(crate) fn parse_template(template: &str) -> Vec<Segment> {
+    assert!(template.starts_with('/'), "path template should start with a slash: {:?}", template);
+
+    let path = &template[1..];
+    if path.is_empty() {
+        return Vec::new();
+    }
+
+    let count = path.split('/').count();
+    path
+        .split('/')
+        .enumerate()
+        .map(|(i, seg)| {
+            assert!(!seg.is_empty(), "path template segments should not be empty: {:?}", template);
+            if seg == "*" {
+                assert!(i + 1 == count, "path template `*` should be the last segment: {:?}", template);
+                Segment::Tail
+            } else if seg.starts_with('{') && seg.ends_with('}') {
+                let name = &seg[1..seg.len() - 1];
+                assert!(!name.is_empty(), "path template parameters should be named: {:?}", template);
+                Segment::Param(name)
+            } else {
+                assert!(!seg.contains(|c| c == '{' || c == '}'), "path template segment is invalid: {:?}", template);
+                Segment::Static(seg)
+            }
+        })
+        .collect()
+}
+
+// Percent-decodes a path segment, only allocating if needed.
+pub(crate) fn decode(seg: &str) -> Result<Cow<str>, Rejection> {
+    if !seg.contains('%') {
+        return Ok(Cow::Borrowed(seg));
+    }
+    urlencoding::decode(seg)
+        .map(Cow::Owned)
+        .map_err(|err| {
+            debug!("invalid percent-encoding in segment {:?}: {:?}", seg, err);
+            reject::not_found()
+        })
+}
+
 fn segment<F, U>(func: F) -> impl Filter<Extract=U, Error=Rejection> + Copy
 where
     F: Fn(&str) -> Result<U, Rejection> + Copy,
@@ -460,6 +1174,21 @@ fn path_and_query(route: &Route) -> PathAndQuery {
 /// path segments exactly, and type identifiers are used just like
 /// [`param`](filters::path::param) filters.
 ///
+/// The path only has to start with these segments, so `path!("foo")`
+/// matches `/foo` and also `/foo/bar`. A suffix changes how it ends:
+///
+/// - `path!("about"; end)` requires the path to end, as with
+///   [`end`](filters::path::end), so it matches `/about` and `/about/`,
+///   but not `/about/us`.
+/// - `path!("about"; strict)`, `; lenient` or `; redirect` ends the path
+///   with that [`TrailingSlash`](filters::path::TrailingSlash) policy,
+///   using [`end_with`](filters::path::end_with). `; end` is the same as
+///   `; lenient`.
+/// - `path!("static" / ..)` extracts the rest of the path as a
+///   [`Tail`](filters::path::Tail), like [`tail`](filters::path::tail).
+/// - `path!("api" / "v1"; prefix)` is the same as no suffix, to spell out
+///   that the rest of the path is left for later filters.
+///
 /// # Example
 ///
 /// ```
@@ -488,15 +1217,74 @@ fn path_and_query(route: &Route) -> PathAndQuery {
 /// ```
 ///
 /// In fact, this is exactly what the macro expands to.
+///
+/// The other endings look like this:
+///
+/// ```
+/// # #[macro_use] extern crate warp; fn main() {
+/// use warp::Filter;
+///
+/// // Match `/static/...`
+/// let files = path!("static" / ..)
+///     .map(|tail: warp::path::Tail| {
+///         format!("You asked for {}", tail.as_str())
+///     });
+///
+/// // Match `/api/v1/users` only
+/// let users = path!("api" / "v1" / "users"; end)
+///     .map(|| "users");
+///
+/// // Match `/api/v1/...`, and mount routes below it
+/// let api = path!("api" / "v1"; prefix)
+///     .and(path!("teams"; end).map(|| "teams"));
+///
+/// // Match `/about`, and redirect `/about/` to it
+/// let about = path!("about"; redirect)
+///     .map(|| "About us");
+/// # }
+/// ```
 #[macro_export]
 macro_rules! path {
-    (@start $first:tt $(/ $tail:tt)*) => ({
+    (@start [$($seg:tt)*] ..) => (
+        path!(@end [$($seg)*] $crate::path::tail())
+    );
+    (@start [$($seg:tt)*] $next:tt / $($rest:tt)+) => (
+        path!(@start [$($seg)* $next] $($rest)+)
+    );
+    (@start [$($seg:tt)*] $last:tt ; prefix) => (
+        path!(@prefix $($seg)* $last)
+    );
+    (@start [$($seg:tt)*] $last:tt ; end) => (
+        path!(@end [$($seg)* $last] $crate::path::end())
+    );
+    (@start [$($seg:tt)*] $last:tt ; $policy:ident) => (
+        path!(@end [$($seg)* $last] $crate::path::end_with(path!(@policy $policy)))
+    );
+    (@start [$($seg:tt)*] $last:tt) => (
+        path!(@prefix $($seg)* $last)
+    );
+    (@end [] $end:expr) => (
+        $end
+    );
+    (@end [$($seg:tt)+] $end:expr) => (
+        $crate::Filter::and(path!(@prefix $($seg)+), $end)
+    );
+    (@prefix $first:tt $($tail:tt)*) => ({
         let __p = path!(@segment $first);
         $(
         let __p = $crate::Filter::and(__p, path!(@segment $tail));
         )*
         __p
     });
+    (@policy strict) => (
+        $crate::path::TrailingSlash::Strict
+    );
+    (@policy lenient) => (
+        $crate::path::TrailingSlash::Lenient
+    );
+    (@policy redirect) => (
+        $crate::path::TrailingSlash::Redirect
+    );
     (@segment $param:ty) => (
         $crate::path::param::<$param>()
     );
@@ -504,7 +1292,7 @@ macro_rules! path {
         $crate::path($s)
     );
     ($($pieces:tt)*) => (
-        path!(@start $($pieces)*)
+        path!(@start [] $($pieces)*)
     );
 }
 
@@ -0,0 +1,118 @@
+//! Reloadable Filters
+
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use futures::Future;
+
+use ::filter::{BoxedFilter, Filter, FilterBase, Tuple};
+use ::reject::Rejection;
+use ::routes::Description;
+
+/// Creates a `Filter` that delegates to a `BoxedFilter`, which can be
+/// replaced at runtime with the returned [`ReloadHandle`](ReloadHandle).
+///
+/// Each request is handled by the filter that was current when it arrived.
+/// Requests already in-flight during a reload finish on the old filter,
+/// while new ones use the new filter.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let (handle, routes) = warp::reloadable(
+///     warp::path("v1")
+///         .map(|| "version 1")
+///         .boxed()
+/// );
+///
+/// // Later, such as when a config file changes...
+/// handle.reload(
+///     warp::path("v2")
+///         .map(|| "version 2")
+///         .boxed()
+/// );
+/// # drop(routes);
+/// ```
+pub fn reloadable<T>(initial: BoxedFilter<T>) -> (ReloadHandle<T>, impl Filter<Extract=T, Error=Rejection> + Clone)
+where
+    T: Tuple + Send + 'static,
+{
+    let current = Arc::new(RwLock::new(initial));
+    let handle = ReloadHandle {
+        current: current.clone(),
+    };
+    (handle, Reloadable { current })
+}
+
+/// A handle to replace the filter of a [`reloadable`](reloadable) filter.
+pub struct ReloadHandle<T: Tuple> {
+    current: Arc<RwLock<BoxedFilter<T>>>,
+}
+
+impl<T: Tuple> ReloadHandle<T> {
+    /// Replaces the filter used for new requests.
+    pub fn reload(&self, filter: BoxedFilter<T>) {
+        let old = {
+            let mut current = self.current
+                .write()
+                .unwrap_or_else(|e| e.into_inner());
+            ::std::mem::replace(&mut *current, filter)
+        };
+        // Dropped outside of the lock, to keep requests waiting as
+        // little as possible.
+        drop(old);
+        debug!("reloadable filter replaced");
+    }
+}
+
+impl<T: Tuple> Clone for ReloadHandle<T> {
+    fn clone(&self) -> ReloadHandle<T> {
+        ReloadHandle {
+            current: self.current.clone(),
+        }
+    }
+}
+
+impl<T: Tuple> fmt::Debug for ReloadHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ReloadHandle")
+            .finish()
+    }
+}
+
+struct Reloadable<T: Tuple> {
+    current: Arc<RwLock<BoxedFilter<T>>>,
+}
+
+impl<T: Tuple> Clone for Reloadable<T> {
+    fn clone(&self) -> Reloadable<T> {
+        Reloadable {
+            current: self.current.clone(),
+        }
+    }
+}
+
+impl<T: Tuple + Send> FilterBase for Reloadable<T> {
+    type Extract = T;
+    type Error = Rejection;
+    type Future = Box<Future<Item=T, Error=Rejection> + Send>;
+
+    #[inline]
+    fn filter(&self) -> Self::Future {
+        // The returned future doesn't borrow the filter, so the lock is
+        // only held while starting it.
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .filter()
+    }
+
+    fn description(&self) -> Description {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .description()
+    }
+}
@@ -0,0 +1,478 @@
+//! Router Filters
+//!
+//! Chaining many routes with [`or`](::Filter::or) means each request tries
+//! them one after another, until one matches. For large APIs, a
+//! [`Router`](Router) instead looks up the routes matching a request path
+//! in a prefix tree, and only tries those.
+//!
+//! # Example
+//!
+//! ```
+//! use warp::Filter;
+//! use warp::http::Method;
+//!
+//! let routes = warp::router()
+//!     .route(Method::GET, "/users", warp::any().map(|| "all users"))
+//!     .route(Method::GET, "/users/{id}", warp::router::param("id").map(|id: u32| {
+//!         format!("user #{}", id)
+//!     }))
+//!     .route(Method::POST, "/users", warp::body::json().map(|_: ::std::collections::HashMap<String, String>| {
+//!         "created"
+//!     }))
+//!     .route(Method::GET, "/static/*", warp::fs::dir("./static"));
+//! ```
+//!
+//! # Reverse Routing
+//!
+//! Routes can be [named](Router::name), so their URLs can be built with
+//! [`warp::routes::urls`](::routes::urls), instead of being hardcoded:
+//!
+//! ```
+//! use warp::Filter;
+//! use warp::http::Method;
+//!
+//! let routes = warp::router()
+//!     .route(Method::GET, "/users/{id}", warp::router::param("id").map(|id: u32| {
+//!         format!("user #{}", id)
+//!     }))
+//!     .name("user_detail");
+//!
+//! let urls = warp::routes::urls(&routes);
+//! let user_detail = urls.get::<(u32,)>("user_detail").unwrap();
+//! assert_eq!(user_detail.url((5,)).unwrap(), "/users/5");
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use futures::{Async, Future, Poll};
+use http::Method;
+
+use ::filter::{BoxedFilter, Filter, FilterBase, filter_fn_one, One};
+use ::filters::path::{self, parse_template, Segment};
+use ::reject::{self, CombineRejection, Rejection};
+use ::reply::{Reply, Response};
+use ::route::{self, Snapshot};
+use ::routes::Description;
+
+/// Creates a new, empty [`Router`](Router).
+pub fn router() -> Router {
+    Router {
+        tree: Arc::new(Tree::default()),
+    }
+}
+
+/// Extract a named parameter captured by a [`Router`](Router) path
+/// template.
+///
+/// The parameter, such as `id` in `/users/{id}`, is percent-decoded and
+/// parsed with `FromStr`. If the parameter wasn't captured, or could not be
+/// decoded or parsed, rejects with a `404 Not Found`, like
+/// [`warp::path::param`](::path::param).
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::http::Method;
+///
+/// let routes = warp::router()
+///     .route(Method::GET, "/sum/{a}/{b}", warp::router::param("a")
+///         .and(warp::router::param("b"))
+///         .map(|a: u32, b: u32| {
+///             format!("{} + {} = {}", a, b, a + b)
+///         }));
+/// ```
+pub fn param<T: FromStr + Send>(name: &'static str) -> impl Filter<Extract=One<T>, Error=Rejection> + Copy {
+    filter_fn_one(move |route| {
+        let value = route.param(name);
+        trace!("router param {:?}: {:?}", name, value);
+        let value = value.ok_or_else(reject::not_found)?;
+        T::from_str(&path::decode(value)?)
+            .map_err(|_| reject::not_found())
+    })
+}
+
+/// A `Filter` dispatching requests to routes, by method and path template.
+///
+/// Create one with [`warp::router()`](router), and add routes to it with
+/// [`route`](Router::route).
+///
+/// A router behaves like the routes chained with `or`, in the order they
+/// were added, where each route is the filter:
+///
+/// ```text
+/// <path template>
+///     .and(<method>)
+///     .and(filter)
+/// ```
+///
+/// That is, when no route matches the path, the request is rejected with a
+/// `404 Not Found`. If routes match the path but not the method, it's
+/// rejected with a `405 Method Not Allowed`. Rejections of the filters
+/// themselves are combined the same way `or` does.
+///
+/// The difference is that only routes matching the path are ever tried,
+/// found in time proportional to the number of path segments, instead of
+/// the number of routes.
+///
+/// # Path Templates
+///
+/// A template is a list of segments, starting with a `/`:
+///
+/// - A literal segment, like `users`, matches exactly that segment.
+/// - A parameter, like `{id}`, matches any non-empty segment, which can be
+///   extracted with [`warp::router::param`](param).
+/// - A final `*` matches the rest of the path, which is left for the filter
+///   to match, such as with [`warp::path::tail`](::path::tail).
+///
+/// Otherwise, a template must match the whole path. A trailing slash in
+/// the request path is ignored.
+///
+/// The path matched by a `Router` is the part not already matched by
+/// previous filters, so a router can be mounted below a prefix.
+#[derive(Clone)]
+pub struct Router {
+    tree: Arc<Tree>,
+}
+
+#[derive(Clone, Default)]
+struct Tree {
+    entries: Vec<Entry>,
+    root: Node,
+}
+
+#[derive(Clone)]
+struct Entry {
+    filter: BoxedFilter<One<Response>>,
+    method: Method,
+    names: Vec<String>,
+    params: Vec<Arc<str>>,
+    template: String,
+}
+
+#[derive(Clone, Default)]
+struct Node {
+    // Routes ending at this node.
+    exact: Vec<usize>,
+    param: Option<Box<Node>>,
+    statics: HashMap<String, Node>,
+    // Routes ending at this node with a `*`.
+    tail: Vec<usize>,
+}
+
+impl Router {
+    /// Adds a route for requests with the `method`, and a path matching the
+    /// `template`.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the `template` is not valid.
+    pub fn route<F, R>(mut self, method: Method, template: &str, filter: F) -> Self
+    where
+        F: Filter<Extract=One<R>> + Send + Sync + 'static,
+        R: Reply,
+        Rejection: From<F::Error>,
+    {
+        let segments = parse_template(template);
+
+        let tree = Arc::make_mut(&mut self.tree);
+        let index = tree.entries.len();
+
+        let mut params = Vec::new();
+        let mut node = &mut tree.root;
+        let mut tail = false;
+        for segment in segments {
+            node = match segment {
+                Segment::Static(s) => node.statics
+                    .entry(s.to_owned())
+                    .or_insert_with(Node::default),
+                Segment::Param(name) => {
+                    params.push(Arc::from(name));
+                    node.param.get_or_insert_with(Box::default)
+                },
+                Segment::Tail => {
+                    tail = true;
+                    break;
+                },
+            };
+        }
+        if tail {
+            node.tail.push(index);
+        } else {
+            node.exact.push(index);
+        }
+
+        tree.entries.push(Entry {
+            filter: filter
+                .map(|reply: R| reply.into_response())
+                .boxed(),
+            method,
+            names: Vec::new(),
+            params,
+            template: template.to_owned(),
+        });
+        self
+    }
+
+    /// Names the route added last, so its URL can be built with
+    /// [`warp::routes::urls`](::routes::urls).
+    ///
+    /// Several routes can share a name, such as the `GET` and `PUT` routes
+    /// of a resource, if they have the same template. Template parameters
+    /// are filled in the order they appear in the template.
+    ///
+    /// # Panics
+    ///
+    /// This panics if no route was added yet, if the name is already used
+    /// by a route with a different template, or if the template has the
+    /// same parameter twice, so mistakes are caught at startup.
+    pub fn name(mut self, name: &str) -> Self {
+        let tree = Arc::make_mut(&mut self.tree);
+        let template = {
+            let entry = tree.entries
+                .last_mut()
+                .expect("Router::name called before any route was added");
+            entry.names.push(name.to_owned());
+            entry.template.clone()
+        };
+
+        let mut params = Vec::new();
+        for segment in parse_template(&template) {
+            if let Segment::Param(param) = segment {
+                assert!(!params.contains(&param), "router template parameter `{}` is repeated: {:?}", param, template);
+                params.push(param);
+            }
+        }
+
+        let existing = tree.entries
+            .iter()
+            .find(|entry| entry.template != template && entry.names.iter().any(|n| n == name));
+        if let Some(existing) = existing {
+            panic!("router name {:?} is already used for {:?}", name, existing.template);
+        }
+        self
+    }
+}
+
+/// A route matching the path of a request.
+struct Candidate {
+    // The captured parameters, as ranges of the unmatched path.
+    captures: Vec<(usize, usize)>,
+    // How much of the unmatched path the template matched.
+    end: usize,
+    entry: usize,
+}
+
+impl Tree {
+    fn candidates(&self, path: &str) -> Vec<Candidate> {
+        let mut segments = Vec::new();
+        if !path.is_empty() {
+            let mut start = 0;
+            for seg in path.split('/') {
+                segments.push((start, start + seg.len()));
+                start += seg.len() + 1;
+            }
+            // `/users/` matches like `/users`.
+            if segments.last().map(|&(start, _)| start == path.len()).unwrap_or(false) {
+                segments.pop();
+            }
+        }
+
+        let mut out = Vec::new();
+        let mut captures = Vec::new();
+        self.root.collect(path, &segments, 0, &mut captures, &mut out);
+        // Try them in the order they were added, like `or` would.
+        out.sort_by_key(|c| c.entry);
+        out
+    }
+}
+
+impl Node {
+    fn collect(
+        &self,
+        path: &str,
+        segments: &[(usize, usize)],
+        depth: usize,
+        captures: &mut Vec<(usize, usize)>,
+        out: &mut Vec<Candidate>,
+    ) {
+        let matched = if depth == 0 { 0 } else { segments[depth - 1].1 };
+        for &entry in &self.tail {
+            out.push(Candidate {
+                captures: captures.clone(),
+                end: matched,
+                entry,
+            });
+        }
+
+        if depth == segments.len() {
+            for &entry in &self.exact {
+                out.push(Candidate {
+                    captures: captures.clone(),
+                    end: path.len(),
+                    entry,
+                });
+            }
+            return;
+        }
+
+        let (start, end) = segments[depth];
+        let seg = &path[start..end];
+        if let Some(child) = self.statics.get(seg) {
+            child.collect(path, segments, depth + 1, captures, out);
+        }
+        if let Some(ref child) = self.param {
+            if !seg.is_empty() {
+                captures.push((start, end));
+                child.collect(path, segments, depth + 1, captures, out);
+                captures.pop();
+            }
+        }
+    }
+}
+
+impl FilterBase for Router {
+    type Extract = One<Response>;
+    type Error = Rejection;
+    type Future = RouterFuture;
+
+    fn filter(&self) -> Self::Future {
+        let (candidates, method, original, path_index) = route::with(|route| {
+            let candidates = self.tree.candidates(route.path());
+            trace!("router candidates: {}", candidates.len());
+            (
+                candidates,
+                route.method().clone(),
+                route.snapshot(),
+                route.matched_path_index(),
+            )
+        });
+
+        RouterFuture {
+            candidates: candidates.into_iter(),
+            current: None,
+            err: None,
+            method,
+            original,
+            path_index,
+            tree: self.tree.clone(),
+        }
+    }
+
+    fn description(&self) -> Description {
+        self.tree.entries
+            .iter()
+            .map(|entry| {
+                let path = parse_template(&entry.template)
+                    .into_iter()
+                    .map(|seg| match seg {
+                        Segment::Static(s) => Description::Path(s.to_owned()),
+                        Segment::Param(name) => Description::Capture(name.to_owned()),
+                        Segment::Tail => Description::Tail,
+                    })
+                    .fold(Description::Any, Description::and);
+                let path = if entry.template.ends_with("/*") {
+                    path
+                } else {
+                    Description::and(path, Description::End)
+                };
+                let path = entry.names
+                    .iter()
+                    .fold(path, |path, name| Description::and(path, Description::Name(name.clone())));
+                let method = Description::Method(entry.method.clone());
+                Description::and(Description::and(path, method), entry.filter.description())
+            })
+            .fold(None, |routes, route| match routes {
+                Some(routes) => Some(Description::or(routes, route)),
+                None => Some(route),
+            })
+            .unwrap_or(Description::Opaque)
+    }
+}
+
+/// The `Future` of a [`Router`](Router) filter.
+#[allow(missing_debug_implementations)]
+pub struct RouterFuture {
+    candidates: ::std::vec::IntoIter<Candidate>,
+    current: Option<<BoxedFilter<One<Response>> as FilterBase>::Future>,
+    err: Option<Rejection>,
+    method: Method,
+    original: Snapshot,
+    path_index: usize,
+    tree: Arc<Tree>,
+}
+
+impl RouterFuture {
+    fn reject(&mut self, err: Rejection) {
+        self.err = Some(match self.err.take() {
+            Some(prev) => err.combine(prev),
+            None => err,
+        });
+    }
+}
+
+impl Future for RouterFuture {
+    type Item = One<Response>;
+    type Error = Rejection;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        loop {
+            let err = match self.current {
+                Some(ref mut current) => match current.poll() {
+                    Ok(Async::Ready(ex)) => return Ok(Async::Ready(ex)),
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    Err(err) => Some(err),
+                },
+                None => None,
+            };
+
+            if let Some(err) = err {
+                self.current = None;
+                self.original.reset();
+                self.reject(err);
+            }
+
+            let candidate = match self.candidates.next() {
+                Some(candidate) => candidate,
+                None => {
+                    let err = self.err
+                        .take()
+                        .unwrap_or_else(reject::not_found);
+                    return Err(err);
+                },
+            };
+
+            let tree = self.tree.clone();
+            let entry = &tree.entries[candidate.entry];
+            if entry.method != self.method {
+                self.reject(reject::method_not_allowed(entry.method.clone()));
+                continue;
+            }
+
+            trace!("router trying {} {}", entry.method, entry.template);
+            let path_index = self.path_index;
+            route::with(|route| {
+                for (name, &(start, end)) in entry.params.iter().zip(candidate.captures.iter()) {
+                    route.push_param(name.clone(), path_index + start, path_index + end);
+                }
+                if candidate.end > 0 {
+                    route.set_unmatched_path(candidate.end);
+                }
+            });
+            self.current = Some(entry.filter.filter());
+        }
+    }
+}
+
+impl fmt::Debug for Router {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut routes = f.debug_list();
+        for entry in &self.tree.entries {
+            routes.entry(&format_args!("{} {}", entry.method, entry.template));
+        }
+        routes.finish()
+    }
+}
@@ -0,0 +1,292 @@
+//! Server-Sent Events (SSE)
+//!
+//! Replies that push a stream of [`Event`](Event)s to the client, as
+//! `text/event-stream`, plus a filter to resume a stream where the client
+//! left off.
+//!
+//! # Example
+//!
+//! ```
+//! # extern crate futures;
+//! # extern crate warp;
+//! # fn main() {
+//! use futures::stream;
+//! use warp::{Filter, Stream};
+//! use warp::sse::Event;
+//!
+//! let ticks = warp::path("ticks")
+//!     .and(warp::sse::last_event_id::<u64>())
+//!     .map(|last_id: Option<u64>| {
+//!         // Resume after the last event the client saw.
+//!         let start = last_id.map_or(0, |id| id + 1);
+//!         let events = stream::iter_ok::<_, ::std::io::Error>(start..start + 10)
+//!             .map(|id| {
+//!                 Event::default()
+//!                     .id(id.to_string())
+//!                     .data(format!("tick {}", id))
+//!             });
+//!         warp::reply::sse(events)
+//!     });
+//! # }
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use futures::{Async, Future, Poll, Stream};
+use http::header::{HeaderValue, CACHE_CONTROL, CONTENT_TYPE};
+use hyper::{Body, Chunk};
+use serde::Serialize;
+use serde_json;
+use tokio::timer::Delay;
+
+use ::filter::{Filter, One};
+use ::reject::Rejection;
+use ::reply::{ReplySealed, Response};
+
+/// Create a `Filter` that extracts the `last-event-id` header, which a
+/// client sends when it reconnects to resume a stream.
+///
+/// Extracts `None` if the header is missing, and rejects if it can't be
+/// parsed as a `T`.
+pub fn last_event_id<T: FromStr + Send>() -> impl Filter<Extract=One<Option<T>>, Error=Rejection> + Copy {
+    ::filters::header::optional_value("last-event-id")
+}
+
+/// Reply with a stream of Server-Sent Events.
+///
+/// Also available as [`warp::reply::sse`](::reply::sse).
+///
+/// A comment is sent every 15 seconds while there are no events, to keep
+/// the connection from timing out, which can be changed with
+/// [`keep_alive`](Sse::keep_alive). Responses include headers telling
+/// caches and proxies not to buffer the stream.
+pub fn reply<S>(events: S) -> Sse<S>
+where
+    S: Stream<Item=Event> + Send + 'static,
+    S::Error: Into<Box<StdError + Send + Sync>>,
+{
+    Sse {
+        events,
+        keep_alive: Some(Duration::from_secs(15)),
+    }
+}
+
+/// A `Reply` streaming Server-Sent Events, created with [`reply`](reply).
+pub struct Sse<S> {
+    events: S,
+    keep_alive: Option<Duration>,
+}
+
+impl<S> Sse<S> {
+    /// Sets how long to wait for an event before sending a keep-alive
+    /// comment.
+    pub fn keep_alive(mut self, interval: Duration) -> Self {
+        self.keep_alive = Some(interval);
+        self
+    }
+
+    /// Disables keep-alive comments.
+    pub fn no_keep_alive(mut self) -> Self {
+        self.keep_alive = None;
+        self
+    }
+}
+
+impl<S> fmt::Debug for Sse<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Sse")
+            .field("keep_alive", &self.keep_alive)
+            .finish()
+    }
+}
+
+impl<S> ReplySealed for Sse<S>
+where
+    S: Stream<Item=Event> + Send + 'static,
+    S::Error: Into<Box<StdError + Send + Sync>>,
+{
+    fn into_response(self) -> Response {
+        let body = Body::wrap_stream(SseBody {
+            events: self.events,
+            keep_alive: self.keep_alive.map(|interval| KeepAlive {
+                interval,
+                delay: Delay::new(Instant::now() + interval),
+            }),
+        });
+
+        let mut res = Response::new(body);
+        {
+            let headers = res.headers_mut();
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+            headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+            // Ask proxies like nginx to send events as they come.
+            headers.insert("x-accel-buffering", HeaderValue::from_static("no"));
+        }
+        res
+    }
+}
+
+/// A single Server-Sent Event.
+///
+/// Built up from the `Default` event, which has no fields set.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::sse::Event;
+///
+/// let event = Event::default()
+///     .event("greeting")
+///     .id("42")
+///     .retry(Duration::from_secs(5))
+///     .data("Hello,\nWorld!");
+///
+/// assert_eq!(
+///     event.to_string(),
+///     "event: greeting\nid: 42\nretry: 5000\ndata: Hello,\ndata: World!\n\n"
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Event {
+    comment: Option<String>,
+    event: Option<String>,
+    id: Option<String>,
+    retry: Option<Duration>,
+    data: Option<String>,
+}
+
+impl Event {
+    /// Sets the data of the event.
+    ///
+    /// Data with several lines is sent as several `data` fields, which the
+    /// client joins back together.
+    pub fn data<D: Into<String>>(mut self, data: D) -> Self {
+        self.data = Some(data.into());
+        self
+    }
+
+    /// Sets the data of the event to a value serialized as JSON.
+    pub fn json_data<T: Serialize>(mut self, data: &T) -> Result<Self, serde_json::Error> {
+        self.data = Some(serde_json::to_string(data)?);
+        Ok(self)
+    }
+
+    /// Sets the name of the event, which clients can listen for.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name contains a newline.
+    pub fn event<E: Into<String>>(mut self, event: E) -> Self {
+        self.event = Some(single_line("event", event.into()));
+        self
+    }
+
+    /// Sets the id of the event, which the client sends back as
+    /// `last-event-id` when it reconnects.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id contains a newline or a null character.
+    pub fn id<I: Into<String>>(mut self, id: I) -> Self {
+        let id = single_line("id", id.into());
+        assert!(!id.contains('\0'), "sse event id must not contain a null character");
+        self.id = Some(id);
+        self
+    }
+
+    /// Sets how long the client should wait before reconnecting, if the
+    /// connection is lost.
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Sets a comment, which clients ignore.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the comment contains a newline.
+    pub fn comment<C: Into<String>>(mut self, comment: C) -> Self {
+        self.comment = Some(single_line("comment", comment.into()));
+        self
+    }
+}
+
+fn single_line(field: &str, value: String) -> String {
+    assert!(
+        !value.contains(|c| c == '\r' || c == '\n'),
+        "sse event {} must not contain a newline",
+        field,
+    );
+    value
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref comment) = self.comment {
+            writeln!(f, ": {}", comment)?;
+        }
+        if let Some(ref event) = self.event {
+            writeln!(f, "event: {}", event)?;
+        }
+        if let Some(ref id) = self.id {
+            writeln!(f, "id: {}", id)?;
+        }
+        if let Some(retry) = self.retry {
+            let millis = retry.as_secs() * 1_000 + u64::from(retry.subsec_nanos() / 1_000_000);
+            writeln!(f, "retry: {}", millis)?;
+        }
+        if let Some(ref data) = self.data {
+            // Any of CRLF, LF, or CR ends a line.
+            for line in data.split("\r\n").flat_map(|line| line.split(|c| c == '\r' || c == '\n')) {
+                writeln!(f, "data: {}", line)?;
+            }
+        }
+        f.write_char('\n')
+    }
+}
+
+struct KeepAlive {
+    interval: Duration,
+    delay: Delay,
+}
+
+struct SseBody<S> {
+    events: S,
+    keep_alive: Option<KeepAlive>,
+}
+
+impl<S> Stream for SseBody<S>
+where
+    S: Stream<Item=Event>,
+    S::Error: Into<Box<StdError + Send + Sync>>,
+{
+    type Item = Chunk;
+    type Error = Box<StdError + Send + Sync>;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        match self.events.poll().map_err(Into::into)? {
+            Async::Ready(Some(event)) => {
+                if let Some(ref mut keep_alive) = self.keep_alive {
+                    keep_alive.delay.reset(Instant::now() + keep_alive.interval);
+                }
+                return Ok(Async::Ready(Some(event.to_string().into())));
+            },
+            Async::Ready(None) => return Ok(Async::Ready(None)),
+            Async::NotReady => (),
+        }
+
+        if let Some(ref mut keep_alive) = self.keep_alive {
+            if keep_alive.delay.poll()?.is_ready() {
+                trace!("sse keep-alive");
+                keep_alive.delay.reset(Instant::now() + keep_alive.interval);
+                return Ok(Async::Ready(Some(Chunk::from(":\n\n"))));
+            }
+        }
+        Ok(Async::NotReady)
+    }
+}
@@ -0,0 +1,110 @@
+//! Unix Domain Socket Filters
+//!
+//! These filters give access to details of connections accepted by a
+//! server running with [`Server::run_unix`](::Server::run_unix) or
+//! [`Server::bind_unix`](::Server::bind_unix).
+
+use std::io;
+
+use libc::{gid_t, pid_t, uid_t};
+use tokio::net::UnixStream;
+
+use ::filter::{Filter, filter_fn_one, One};
+use ::never::Never;
+
+/// Creates a `Filter` to get the credentials of the peer process.
+///
+/// Extracts `None` if the request was not received over a Unix Domain
+/// Socket, or the credentials could not be determined.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::unix::PeerCred;
+///
+/// let route = warp::unix::peer_cred()
+///     .map(|cred: Option<PeerCred>| {
+///         match cred {
+///             Some(cred) => format!("Hello, uid {}!", cred.uid()),
+///             None => "Hello, stranger!".to_string(),
+///         }
+///     });
+/// ```
+pub fn peer_cred() -> impl Filter<Extract=One<Option<PeerCred>>, Error=Never> + Copy {
+    filter_fn_one(|route| {
+        Ok::<_, Never>(route.conn_info().peer_cred)
+    })
+}
+
+/// Credentials of the process on the other end of a Unix Domain Socket.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PeerCred {
+    uid: uid_t,
+    gid: gid_t,
+    pid: Option<pid_t>,
+}
+
+impl PeerCred {
+    /// The user ID of the peer process.
+    pub fn uid(&self) -> uid_t {
+        self.uid
+    }
+
+    /// The group ID of the peer process.
+    pub fn gid(&self) -> gid_t {
+        self.gid
+    }
+
+    /// The process ID of the peer process.
+    ///
+    /// This is only available on Linux and Android.
+    pub fn pid(&self) -> Option<pid_t> {
+        self.pid
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn peer_cred_of(sock: &UnixStream) -> io::Result<PeerCred> {
+    use std::mem;
+    use std::os::unix::io::AsRawFd;
+
+    use libc::{c_void, getsockopt, socklen_t, ucred, SOL_SOCKET, SO_PEERCRED};
+
+    // tokio-uds drops the pid, so ask the socket directly.
+    let mut cred = ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut len = mem::size_of::<ucred>() as socklen_t;
+
+    let ret = unsafe {
+        getsockopt(
+            sock.as_raw_fd(),
+            SOL_SOCKET,
+            SO_PEERCRED,
+            &mut cred as *mut ucred as *mut c_void,
+            &mut len,
+        )
+    };
+
+    if ret == 0 && len as usize == mem::size_of::<ucred>() {
+        Ok(PeerCred {
+            uid: cred.uid,
+            gid: cred.gid,
+            pid: Some(cred.pid),
+        })
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) fn peer_cred_of(sock: &UnixStream) -> io::Result<PeerCred> {
+    sock.peer_cred().map(|cred| PeerCred {
+        uid: cred.uid,
+        gid: cred.gid,
+        pid: None,
+    })
+}
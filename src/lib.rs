@@ -82,22 +82,29 @@
 //!
 //! [Filter]: trait.Filter.html
 
+extern crate brotli;
 extern crate bytes;
+extern crate flate2;
 #[macro_use] extern crate futures;
 extern crate headers_ext as headers;
 #[doc(hidden)]
 pub extern crate http;
 extern crate hyper;
+#[cfg(unix)]
+extern crate libc;
 #[macro_use] extern crate log as logcrate;
 extern crate mime;
 extern crate mime_guess;
 #[macro_use] extern crate scoped_tls;
-extern crate serde;
+#[macro_use] extern crate serde;
 extern crate serde_json;
 extern crate serde_urlencoded;
 extern crate tokio;
 extern crate tokio_io;
+#[cfg(feature = "tls")]
+extern crate tokio_rustls;
 extern crate tokio_threadpool;
+extern crate tower_service;
 extern crate tungstenite;
 extern crate urlencoding;
 
@@ -106,24 +113,31 @@ mod filter;
 pub mod filters;
 mod generic;
 mod never;
+mod proxy;
 pub mod redirect;
 pub mod reject;
 pub mod reply;
 mod route;
+pub mod routes;
 mod server;
 pub mod test;
+#[cfg(feature = "tls")]
+mod tls;
+mod transport;
 
 pub use self::error::Error;
-pub use self::filter::{Filter};
+pub use self::filter::{Filter, FilteredService, service};
 // This otherwise shows a big dump of re-exports in the doc homepage,
 // with zero context, so just hide it from the docs. Doc examples
 // on each can show that a convenient import exists.
 #[doc(hidden)]
 #[allow(deprecated)]
 pub use self::filters::{
+    addr,
     // any() function
     any::any,
     body,
+    compression,
     cookie,
     // cookie() function
     cookie::cookie,
@@ -135,9 +149,12 @@ pub use self::filters::{
     log,
     // log() function
     log::log,
+    method,
+    // method() function
     method::{get, method, post, put, delete},
     method::{head, options, patch},
     method::{get2, post2, put2, delete2},
+    multipart,
     path,
     // the index() function
     path::index,
@@ -146,18 +163,32 @@ pub use self::filters::{
     query,
     // query() function
     query::query,
+    reload,
+    // reloadable() function
+    reload::reloadable,
+    router,
+    // router() function
+    router::router,
+    sse,
     ws,
     // ws() function
     ws::{ws, ws2},
 };
 #[doc(hidden)]
+#[cfg(unix)]
+pub use self::filters::unix;
+#[doc(hidden)]
 pub use self::redirect::{redirect};
 #[doc(hidden)]
 #[allow(deprecated)]
 pub use self::reject::{reject, Rejection};
 #[doc(hidden)]
 pub use self::reply::{reply, Reply};
-pub use self::server::{serve, Server};
+pub use self::server::{serve, Server, ServerHandle};
+#[cfg(unix)]
+pub use self::server::listen_fds;
+#[cfg(feature = "tls")]
+pub use self::server::TlsServer;
 pub use hyper::rt::spawn;
 
 
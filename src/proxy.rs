@@ -0,0 +1,310 @@
+//! PROXY protocol
+//!
+//! Load balancers like HAProxy or AWS NLBs can send the address of the
+//! original client in a header at the very start of a connection. Both the
+//! human readable version 1, and the binary version 2 are supported.
+//!
+//! See https://www.haproxy.org/download/1.8/doc/proxy-protocol.txt
+
+use std::io::{self, Cursor};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::str;
+use std::time::{Duration, Instant};
+
+use bytes::{Buf, BytesMut};
+use futures::{Async, Future, Poll, Stream};
+use futures::stream::FuturesUnordered;
+use tokio::timer::Delay;
+use tokio_io::AsyncRead;
+
+use ::transport::AddrStream;
+
+const V1_PREFIX: &[u8] = b"PROXY ";
+// The longest a version 1 header can be, including the CRLF.
+const V1_MAX_LEN: usize = 107;
+const V2_SIGNATURE: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+const V2_HEADER_LEN: usize = 16;
+
+/// The result of parsing the start of a connection.
+#[derive(Debug, PartialEq)]
+enum Parsed {
+    /// More bytes are needed.
+    Incomplete,
+    /// A complete header of `len` bytes, with the source and destination
+    /// addresses, if the proxy knew them.
+    Done {
+        len: usize,
+        addrs: Option<(SocketAddr, SocketAddr)>,
+    },
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn parse(buf: &[u8]) -> io::Result<Parsed> {
+    if buf.starts_with(V1_PREFIX) {
+        parse_v1(buf)
+    } else if buf.starts_with(V2_SIGNATURE) {
+        parse_v2(buf)
+    } else if V1_PREFIX.starts_with(buf) || V2_SIGNATURE.starts_with(buf) {
+        Ok(Parsed::Incomplete)
+    } else {
+        Err(invalid("missing PROXY protocol header"))
+    }
+}
+
+fn parse_v1(buf: &[u8]) -> io::Result<Parsed> {
+    let end = match buf.iter().take(V1_MAX_LEN).position(|&b| b == b'\n') {
+        Some(end) => end,
+        None if buf.len() < V1_MAX_LEN => return Ok(Parsed::Incomplete),
+        None => return Err(invalid("PROXY v1 header too long")),
+    };
+
+    if end == 0 || buf[end - 1] != b'\r' {
+        return Err(invalid("PROXY v1 header must end with CRLF"));
+    }
+
+    let line = str::from_utf8(&buf[V1_PREFIX.len()..end - 1])
+        .map_err(|_| invalid("PROXY v1 header is not ASCII"))?;
+    let mut parts = line.split(' ');
+
+    let addrs = match parts.next() {
+        Some("UNKNOWN") => None,
+        Some(proto @ "TCP4") | Some(proto @ "TCP6") => {
+            let mut next = || parts.next().ok_or_else(|| invalid("PROXY v1 header missing field"));
+            let src_ip = next()?.parse::<IpAddr>().map_err(|_| invalid("PROXY v1 invalid source address"))?;
+            let dst_ip = next()?.parse::<IpAddr>().map_err(|_| invalid("PROXY v1 invalid destination address"))?;
+            let src_port = next()?.parse::<u16>().map_err(|_| invalid("PROXY v1 invalid source port"))?;
+            let dst_port = next()?.parse::<u16>().map_err(|_| invalid("PROXY v1 invalid destination port"))?;
+
+            if src_ip.is_ipv4() != (proto == "TCP4") || dst_ip.is_ipv4() != (proto == "TCP4") {
+                return Err(invalid("PROXY v1 address family mismatch"));
+            }
+
+            Some((SocketAddr::new(src_ip, src_port), SocketAddr::new(dst_ip, dst_port)))
+        },
+        _ => return Err(invalid("PROXY v1 unknown protocol")),
+    };
+
+    Ok(Parsed::Done {
+        len: end + 1,
+        addrs,
+    })
+}
+
+fn parse_v2(buf: &[u8]) -> io::Result<Parsed> {
+    if buf.len() < V2_HEADER_LEN {
+        return Ok(Parsed::Incomplete);
+    }
+
+    let version = buf[12] >> 4;
+    let command = buf[12] & 0x0F;
+    let family = buf[13] >> 4;
+    let addrs_len = Cursor::new(&buf[14..16]).get_u16_be() as usize;
+    let len = V2_HEADER_LEN + addrs_len;
+
+    if version != 2 {
+        return Err(invalid("PROXY v2 unknown version"));
+    }
+
+    if buf.len() < len {
+        return Ok(Parsed::Incomplete);
+    }
+
+    let mut block = Cursor::new(&buf[V2_HEADER_LEN..len]);
+
+    let addrs = match command {
+        // LOCAL, such as health checks from the proxy itself.
+        0x0 => None,
+        // PROXY
+        0x1 => match family {
+            // AF_INET
+            0x1 => {
+                if block.remaining() < 12 {
+                    return Err(invalid("PROXY v2 address block too short"));
+                }
+                let src = Ipv4Addr::from(block.get_u32_be());
+                let dst = Ipv4Addr::from(block.get_u32_be());
+                let src_port = block.get_u16_be();
+                let dst_port = block.get_u16_be();
+                Some((
+                    SocketAddr::new(src.into(), src_port),
+                    SocketAddr::new(dst.into(), dst_port),
+                ))
+            },
+            // AF_INET6
+            0x2 => {
+                if block.remaining() < 36 {
+                    return Err(invalid("PROXY v2 address block too short"));
+                }
+                let mut src = [0; 16];
+                block.copy_to_slice(&mut src);
+                let mut dst = [0; 16];
+                block.copy_to_slice(&mut dst);
+                let src_port = block.get_u16_be();
+                let dst_port = block.get_u16_be();
+                Some((
+                    SocketAddr::new(Ipv6Addr::from(src).into(), src_port),
+                    SocketAddr::new(Ipv6Addr::from(dst).into(), dst_port),
+                ))
+            },
+            // AF_UNSPEC or AF_UNIX, which have no IP addresses.
+            _ => None,
+        },
+        _ => return Err(invalid("PROXY v2 unknown command")),
+    };
+
+    Ok(Parsed::Done {
+        len,
+        addrs,
+    })
+}
+
+/// Reads and applies the PROXY protocol header of accepted connections,
+/// yielding them once done.
+pub(crate) struct Handshakes {
+    pending: FuturesUnordered<Handshake>,
+    timeout: Option<Duration>,
+}
+
+impl Handshakes {
+    pub(crate) fn new(timeout: Option<Duration>) -> Handshakes {
+        Handshakes {
+            pending: FuturesUnordered::new(),
+            timeout,
+        }
+    }
+
+    pub(crate) fn push(&mut self, stream: AddrStream) {
+        self.pending.push(Handshake {
+            buf: BytesMut::new(),
+            delay: self.timeout.map(|timeout| Delay::new(Instant::now() + timeout)),
+            stream: Some(stream),
+        });
+    }
+}
+
+impl Stream for Handshakes {
+    type Item = AddrStream;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        loop {
+            match self.pending.poll() {
+                Ok(Async::Ready(Some(stream))) => return Ok(Async::Ready(Some(stream))),
+                // Empty for now, more may be pushed later.
+                Ok(Async::Ready(None)) |
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(err) => {
+                    // Only this connection is dropped.
+                    debug!("PROXY protocol error: {}", err);
+                }
+            }
+        }
+    }
+}
+
+struct Handshake {
+    buf: BytesMut,
+    delay: Option<Delay>,
+    stream: Option<AddrStream>,
+}
+
+impl Future for Handshake {
+    type Item = AddrStream;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if let Some(ref mut delay) = self.delay {
+            if let Ok(Async::Ready(())) = delay.poll() {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "PROXY protocol header timed out"));
+            }
+        }
+
+        loop {
+            match parse(&self.buf)? {
+                Parsed::Done { len, addrs } => {
+                    let mut stream = self.stream
+                        .take()
+                        .expect("Handshake polled after complete");
+                    trace!("PROXY protocol header: {:?}", addrs);
+                    self.buf.advance(len);
+                    stream.set_proxied(addrs, self.buf.take().freeze());
+                    return Ok(Async::Ready(stream));
+                },
+                Parsed::Incomplete => {
+                    self.buf.reserve(V1_MAX_LEN);
+                    let stream = self.stream
+                        .as_mut()
+                        .expect("Handshake polled after complete");
+                    let n = try_ready!(stream.read_buf(&mut self.buf));
+                    if n == 0 {
+                        return Err(io::ErrorKind::UnexpectedEof.into());
+                    }
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Parsed};
+
+    fn addrs(src: &str, dst: &str) -> Option<(::std::net::SocketAddr, ::std::net::SocketAddr)> {
+        Some((src.parse().unwrap(), dst.parse().unwrap()))
+    }
+
+    #[test]
+    fn v1() {
+        let header = b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 443\r\nGET / HTTP/1.1\r\n";
+        assert_eq!(parse(header).unwrap(), Parsed::Done {
+            len: 47,
+            addrs: addrs("192.168.0.1:56324", "192.168.0.11:443"),
+        });
+
+        let header = b"PROXY TCP6 ::1 ::2 1 2\r\n";
+        assert_eq!(parse(header).unwrap(), Parsed::Done {
+            len: header.len(),
+            addrs: addrs("[::1]:1", "[::2]:2"),
+        });
+
+        let header = b"PROXY UNKNOWN\r\n";
+        assert_eq!(parse(header).unwrap(), Parsed::Done {
+            len: header.len(),
+            addrs: None,
+        });
+
+        assert_eq!(parse(b"PRO").unwrap(), Parsed::Incomplete);
+        assert_eq!(parse(b"PROXY TCP4 1.2.3.4").unwrap(), Parsed::Incomplete);
+
+        assert!(parse(b"PROXY TCP4 ::1 ::2 1 2\r\n").is_err());
+        assert!(parse(b"PROXY TCP4 1.2.3.4 5.6.7.8 1\r\n").is_err());
+        assert!(parse(&[b'A'; 120][..]).is_err());
+        assert!(parse(b"GET / HTTP/1.1\r\n").is_err());
+    }
+
+    #[test]
+    fn v2() {
+        let mut header = b"\r\n\r\n\0\r\nQUIT\n\x21\x11\x00\x0c".to_vec();
+        header.extend_from_slice(&[10, 0, 0, 1, 10, 0, 0, 2, 0x1f, 0x90, 0x01, 0xbb]);
+        header.extend_from_slice(b"GET");
+        assert_eq!(parse(&header).unwrap(), Parsed::Done {
+            len: 28,
+            addrs: addrs("10.0.0.1:8080", "10.0.0.2:443"),
+        });
+
+        assert_eq!(parse(&header[..20]).unwrap(), Parsed::Incomplete);
+
+        // LOCAL, with a TLV that is skipped.
+        let header = b"\r\n\r\n\0\r\nQUIT\n\x20\x00\x00\x03\x01\x00\x00";
+        assert_eq!(parse(header).unwrap(), Parsed::Done {
+            len: 19,
+            addrs: None,
+        });
+
+        // version 3
+        assert!(parse(b"\r\n\r\n\0\r\nQUIT\n\x31\x11\x00\x00").is_err());
+    }
+}
@@ -30,7 +30,7 @@
 use std::error::Error as StdError;
 use std::fmt;
 
-use http::{self, header::{CONTENT_TYPE, HeaderValue}, StatusCode};
+use http::{self, header::{ALLOW, CONTENT_TYPE, HeaderValue, LOCATION}, Method, StatusCode};
 use hyper::Body;
 use serde;
 use serde_json;
@@ -72,10 +72,27 @@ pub fn not_found() -> Rejection {
     }
 }
 
-// 405 Method Not Allowed
+// 405 Method Not Allowed, remembering which method would have matched
 #[inline]
-pub(crate) fn method_not_allowed() -> Rejection {
-    known(MethodNotAllowed)
+pub(crate) fn method_not_allowed(allowed: Method) -> Rejection {
+    known(MethodNotAllowed {
+        allowed: vec![allowed],
+    })
+}
+
+// 301 Moved Permanently, or 308 Permanent Redirect, to the path without a
+// trailing slash
+#[inline]
+pub(crate) fn trailing_slash(location: String, method: &Method) -> Rejection {
+    let status = if method == Method::GET || method == Method::HEAD {
+        StatusCode::MOVED_PERMANENTLY
+    } else {
+        StatusCode::PERMANENT_REDIRECT
+    };
+    known(TrailingSlash {
+        location,
+        status,
+    })
 }
 
 // 411 Length Required
@@ -99,6 +116,14 @@ pub(crate) fn unsupported_media_type() -> Rejection {
     known(UnsupportedMediaType)
 }
 
+// 408 Request Timeout, or 503 Service Unavailable
+#[inline]
+pub(crate) fn timed_out(reading_body: bool) -> Rejection {
+    known(TimedOut {
+        reading_body,
+    })
+}
+
 #[doc(hidden)]
 #[deprecated(note = "use warp::reject::custom and Filter::recover to send a 500 error")]
 pub fn server_error() -> Rejection {
@@ -211,6 +236,16 @@ impl Rejection {
         Reject::status(self)
     }
 
+    /// The methods that would have been allowed, if this rejection was
+    /// because of the request method.
+    pub(crate) fn allowed_methods(&self) -> Vec<Method> {
+        let mut allowed = Vec::new();
+        if let Reason::Other(ref rejections) = self.reason {
+            rejections.allowed_methods(&mut allowed);
+        }
+        allowed
+    }
+
     #[doc(hidden)]
     #[deprecated(note = "Custom rejections should use `warp::reject::custom()`.")]
     pub fn with<E>(self, err: E) -> Self
@@ -307,7 +342,18 @@ impl Reject for Rejection {
                 *res.status_mut() = StatusCode::NOT_FOUND;
                 res
             },
-            Reason::Other(ref other) => other.into_response(),
+            Reason::Other(ref other) => {
+                let mut res = other.into_response();
+                // A 405 must list the methods that are allowed, from all of
+                // the rejections for this path.
+                if res.status() == StatusCode::METHOD_NOT_ALLOWED {
+                    let allowed = self.allowed_methods();
+                    if let Some(value) = allow_header(&allowed) {
+                        res.headers_mut().insert(ALLOW, value);
+                    }
+                }
+                res
+            },
         }
     }
 
@@ -371,12 +417,22 @@ impl Rejections {
                     StatusCode::BAD_REQUEST
                 } else if e.is::<::query::InvalidQuery>() {
                     StatusCode::BAD_REQUEST
+                } else if e.is::<::path::InvalidParam>() {
+                    StatusCode::BAD_REQUEST
+                } else if let Some(e) = e.downcast_ref::<TrailingSlash>() {
+                    e.status
                 } else if e.is::<LengthRequired>() {
                     StatusCode::LENGTH_REQUIRED
                 } else if e.is::<PayloadTooLarge>() {
                     StatusCode::PAYLOAD_TOO_LARGE
                 } else if e.is::<UnsupportedMediaType>() {
                     StatusCode::UNSUPPORTED_MEDIA_TYPE
+                } else if let Some(e) = e.downcast_ref::<TimedOut>() {
+                    if e.reading_body {
+                        StatusCode::REQUEST_TIMEOUT
+                    } else {
+                        StatusCode::SERVICE_UNAVAILABLE
+                    }
                 } else if e.is::<::body::BodyReadError>() {
                     StatusCode::BAD_REQUEST
                 } else if e.is::<::body::BodyDeserializeError>() {
@@ -389,6 +445,8 @@ impl Rejections {
                     StatusCode::INTERNAL_SERVER_ERROR
                 } else if e.is::<::body::BodyConsumedMultipleTimes>() {
                     StatusCode::INTERNAL_SERVER_ERROR
+                } else if e.is::<::multipart::SpoolError>() {
+                    StatusCode::INTERNAL_SERVER_ERROR
                 } else if e.is::<::fs::FsNeedsTokioThreadpool>() {
                     StatusCode::INTERNAL_SERVER_ERROR
                 } else {
@@ -414,6 +472,11 @@ impl Rejections {
                 let mut res = http::Response::new(Body::from(e.to_string()));
                 *res.status_mut() = self.status();
                 res.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+                if let Some(e) = e.downcast_ref::<TrailingSlash>() {
+                    if let Ok(location) = HeaderValue::from_str(&e.location) {
+                        res.headers_mut().insert(LOCATION, location);
+                    }
+                }
                 res
             },
             Rejections::KnownStatus(ref s) => {
@@ -463,6 +526,33 @@ impl Rejections {
         }
     }
 
+    fn allowed_methods(&self, allowed: &mut Vec<Method>) {
+        match *self {
+            Rejections::Known(ref e) => {
+                if let Some(e) = e.downcast_ref::<MethodNotAllowed>() {
+                    for method in &e.allowed {
+                        if !allowed.contains(method) {
+                            allowed.push(method.clone());
+                        }
+                    }
+                }
+            },
+            Rejections::With(ref rej, _) => {
+                if let Reason::Other(ref rejections) = rej.reason {
+                    rejections.allowed_methods(allowed);
+                }
+            },
+            Rejections::KnownStatus(_) |
+            Rejections::Custom(_) => (),
+            Rejections::Combined(ref a, ref b) => {
+                // `b` is the earlier rejection, keep the order routes were
+                // tried in.
+                b.allowed_methods(allowed);
+                a.allowed_methods(allowed);
+            },
+        }
+    }
+
     pub fn find_cause<T: StdError + 'static>(&self) -> Option<&T> {
         match *self {
             Rejections::Known(ref e) => {
@@ -486,6 +576,40 @@ impl Rejections {
 }
 
 
+// Two method rejections for the same path are merged into one, with both
+// sets of allowed methods. Anything else is kept as `Combined`.
+fn combined(left: Box<Rejections>, right: Box<Rejections>) -> Box<Rejections> {
+    if let (&Rejections::Known(ref l), &Rejections::Known(ref r)) = (&*left, &*right) {
+        if let (Some(l), Some(r)) = (l.downcast_ref::<MethodNotAllowed>(), r.downcast_ref::<MethodNotAllowed>()) {
+            // `right` is the earlier rejection, keep the order routes were
+            // tried in.
+            let mut allowed = r.allowed.clone();
+            for method in &l.allowed {
+                if !allowed.contains(method) {
+                    allowed.push(method.clone());
+                }
+            }
+            return Box::new(Rejections::Known(Box::new(MethodNotAllowed {
+                allowed,
+            })));
+        }
+    }
+    Box::new(Rejections::Combined(left, right))
+}
+
+// The `allow` header for a 405, or an `OPTIONS` response.
+pub(crate) fn allow_header(methods: &[Method]) -> Option<HeaderValue> {
+    if methods.is_empty() {
+        return None;
+    }
+    let value = methods
+        .iter()
+        .map(Method::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+    HeaderValue::from_str(&value).ok()
+}
+
 fn preferred<'a>(a: &'a Rejections, b: &'a Rejections) -> &'a Rejections {
     // Compare status codes, with this priority:
     // - NOT_FOUND is lowest
@@ -533,7 +657,9 @@ impl fmt::Debug for Rejections {
 }
 
 #[derive(Debug)]
-struct MethodNotAllowed;
+struct MethodNotAllowed {
+    allowed: Vec<Method>,
+}
 
 impl fmt::Display for MethodNotAllowed {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -547,6 +673,24 @@ impl StdError for MethodNotAllowed {
     }
 }
 
+#[derive(Debug)]
+struct TrailingSlash {
+    location: String,
+    status: StatusCode,
+}
+
+impl fmt::Display for TrailingSlash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Moved to {}", self.location)
+    }
+}
+
+impl StdError for TrailingSlash {
+    fn description(&self) -> &str {
+        "Moved to the path without a trailing slash"
+    }
+}
+
 #[derive(Debug)]
 struct LengthRequired;
 
@@ -563,7 +707,7 @@ impl StdError for LengthRequired {
 }
 
 #[derive(Debug)]
-struct PayloadTooLarge;
+pub(crate) struct PayloadTooLarge;
 
 impl fmt::Display for PayloadTooLarge {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -592,6 +736,60 @@ impl StdError for UnsupportedMediaType {
     }
 }
 
+/// A request took too long, and was rejected.
+///
+/// This is the cause of rejections made by [`Filter::timeout`](::Filter::timeout)
+/// and the [`Server::request_timeout`](::Server::request_timeout).
+///
+/// These are `408 Request Timeout` if the request body was still being
+/// read, and otherwise `503 Service Unavailable`.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::Filter;
+///
+/// let route = warp::any()
+///     .map(warp::reply)
+///     .timeout(Duration::from_secs(30))
+///     .recover(|rejection: warp::Rejection| {
+///         if rejection.find_cause::<warp::reject::TimedOut>().is_some() {
+///             Ok("Sorry, that took too long.")
+///         } else {
+///             Err(rejection)
+///         }
+///     });
+/// ```
+#[derive(Debug)]
+pub struct TimedOut {
+    reading_body: bool,
+}
+
+impl TimedOut {
+    /// Returns whether the request body was still being read when the
+    /// timeout expired.
+    pub fn is_reading_body(&self) -> bool {
+        self.reading_body
+    }
+}
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.reading_body {
+            f.write_str("Timed out reading the request body")
+        } else {
+            f.write_str("Timed out handling the request")
+        }
+    }
+}
+
+impl StdError for TimedOut {
+    fn description(&self) -> &str {
+        "Request timed out"
+    }
+}
+
 trait Typed: StdError + 'static {
     fn type_id(&self) -> ::std::any::TypeId;
 }
@@ -600,7 +798,7 @@ mod sealed {
     use std::fmt;
     use http::StatusCode;
     use ::never::Never;
-    use super::{Cause, Reason, Rejection, Rejections};
+    use super::{combined, Cause, Reason, Rejection};
 
     pub trait Reject: fmt::Debug + Send + Sync {
         fn status(&self) -> StatusCode;
@@ -624,7 +822,7 @@ mod sealed {
         fn combine(self, other: Rejection) -> Self::Rejection {
             let reason = match (self.reason, other.reason) {
                 (Reason::Other(left), Reason::Other(right)) => {
-                    Reason::Other(Box::new(Rejections::Combined(left, right)))
+                    Reason::Other(combined(left, right))
                 },
                 (Reason::Other(other), Reason::NotFound) |
                 (Reason::NotFound, Reason::Other(other)) => {
@@ -680,7 +878,7 @@ mod tests {
         assert_eq!(bad_request().status(), StatusCode::BAD_REQUEST);
         assert_eq!(forbidden().status(), StatusCode::FORBIDDEN);
         assert_eq!(not_found().status(), StatusCode::NOT_FOUND);
-        assert_eq!(method_not_allowed().status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(method_not_allowed(Method::GET).status(), StatusCode::METHOD_NOT_ALLOWED);
         assert_eq!(length_required().status(), StatusCode::LENGTH_REQUIRED);
         assert_eq!(payload_too_large().status(), StatusCode::PAYLOAD_TOO_LARGE);
         assert_eq!(unsupported_media_type().status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
@@ -732,6 +930,26 @@ mod tests {
         assert_eq!(reject.cause().unwrap().to_string(), "right");
     }
 
+    #[test]
+    fn combine_method_not_allowed() {
+        // Like `or` does, later rejections are combined with earlier ones.
+        let reject = method_not_allowed(Method::GET)
+            .combine(method_not_allowed(Method::POST)
+                .combine(not_found()
+                    .combine(method_not_allowed(Method::GET))));
+        assert_eq!(reject.allowed_methods(), vec![Method::GET, Method::POST]);
+
+        let resp = reject.into_response();
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(resp.headers()["allow"], "GET, POST");
+
+        // Merged across other rejections too.
+        let reject = method_not_allowed(Method::PUT)
+            .combine(custom("boom")
+                .combine(method_not_allowed(Method::GET)));
+        assert_eq!(reject.allowed_methods(), vec![Method::GET, Method::PUT]);
+    }
+
     #[allow(deprecated)]
     #[test]
     fn unhandled_customs() {
@@ -836,7 +1054,7 @@ mod tests {
 
         let rej = bad_request()
             .with(io::Error::new(io::ErrorKind::Other, "boom"))
-            .combine(method_not_allowed());
+            .combine(method_not_allowed(Method::GET));
 
         assert_eq!(rej.find_cause::<io::Error>().unwrap().to_string(), "boom");
         assert!(rej.find_cause::<MethodNotAllowed>().is_some(), "MethodNotAllowed");
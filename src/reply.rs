@@ -36,6 +36,7 @@
 use std::error::Error as StdError;
 use std::fmt;
 
+use futures::Stream;
 use http::header::{CONTENT_TYPE, HeaderName, HeaderValue};
 use http::{HttpTryFrom, StatusCode};
 use serde::Serialize;
@@ -105,6 +106,39 @@ where
     }
 }
 
+/// Reply with a stream of Server-Sent Events.
+///
+/// See [`warp::sse`](::sse) for building the [`Event`](::sse::Event)s,
+/// and for the keep-alive comments sent between them.
+///
+/// # Example
+///
+/// ```
+/// # extern crate futures;
+/// # extern crate warp;
+/// # fn main() {
+/// use futures::stream;
+/// use warp::Filter;
+/// use warp::sse::Event;
+///
+/// let route = warp::path("events")
+///     .map(|| {
+///         let events = stream::iter_ok::<_, ::std::io::Error>(vec![
+///             Event::default().data("first"),
+///             Event::default().event("last").data("second"),
+///         ]);
+///         warp::reply::sse(events)
+///     });
+/// # }
+/// ```
+pub fn sse<S>(events: S) -> ::sse::Sse<S>
+where
+    S: Stream<Item=::sse::Event> + Send + 'static,
+    S::Error: Into<Box<StdError + Send + Sync>>,
+{
+    ::sse::reply(events)
+}
+
 #[allow(missing_debug_implementations)]
 struct Json {
     inner: Result<Vec<u8>, ()>,
@@ -1,10 +1,15 @@
 use std::cell::RefCell;
 use std::mem;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
+use futures::{Async, Poll, Stream};
 use http;
-use hyper::Body;
+use hyper::{Body, Chunk};
+use hyper::body::Payload;
 
 use ::Request;
+use ::transport::ConnInfo;
 
 scoped_thread_local!(static ROUTE: RefCell<Route>);
 
@@ -32,18 +37,47 @@ where
 #[derive(Debug)]
 pub(crate) struct Route {
     body: BodyState,
+    body_limit: Option<u64>,
+    conn_info: ConnInfo,
+    decode_body: bool,
+    // Named path parameters captured by a router, as ranges of the path.
+    params: Vec<(Arc<str>, usize, usize)>,
     req: Request,
     segments_index: usize,
+    track_body: bool,
+}
+
+// What a filter may change about the route before it rejects, saved so
+// the next filter tried, such as the other side of an `or`, starts from
+// the same place.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Snapshot {
+    body_limit: Option<u64>,
+    decode_body: bool,
+    params_len: usize,
+    segments_index: usize,
+}
+
+impl Snapshot {
+    pub(crate) fn take() -> Snapshot {
+        with(|route| route.snapshot())
+    }
+
+    pub(crate) fn reset(&self) {
+        with(|route| route.reset(*self))
+    }
 }
 
 #[derive(Debug)]
 enum BodyState {
     Ready,
     Taken,
+    // Taken, and still being read until the flag is set.
+    Reading(Arc<AtomicBool>),
 }
 
 impl Route {
-    pub(crate) fn new(req: Request) -> RefCell<Route> {
+    pub(crate) fn new(req: Request, conn_info: ConnInfo) -> RefCell<Route> {
         debug_assert_eq!(
             req.uri().path().as_bytes()[0],
             b'/',
@@ -52,9 +86,14 @@ impl Route {
 
         RefCell::new(Route {
             body: BodyState::Ready,
+            body_limit: None,
+            conn_info,
+            decode_body: false,
+            params: Vec::new(),
             req,
             // always start at 1, since paths are `/...`.
             segments_index: 1,
+            track_body: false,
         })
     }
 
@@ -62,6 +101,10 @@ impl Route {
         self.req.method()
     }
 
+    pub(crate) fn set_method(&mut self, method: http::Method) {
+        *self.req.method_mut() = method;
+    }
+
     pub(crate) fn headers(&self) -> &http::HeaderMap {
         self.req.headers()
     }
@@ -78,6 +121,10 @@ impl Route {
         self.req.extensions_mut()
     }
 
+    pub(crate) fn conn_info(&self) -> &ConnInfo {
+        &self.conn_info
+    }
+
     pub(crate) fn uri(&self) -> &http::Uri {
         self.req.uri()
     }
@@ -107,6 +154,28 @@ impl Route {
         }
     }
 
+    /// Replaces the unmatched part of the path, keeping the query.
+    pub(crate) fn replace_unmatched_path(&mut self, path: &str) {
+        let uri = {
+            let uri = self.req.uri();
+            let mut full = String::with_capacity(self.segments_index + path.len());
+            full.push_str(&uri.path()[..self.segments_index]);
+            full.push_str(path);
+            if let Some(query) = uri.query() {
+                full.push('?');
+                full.push_str(query);
+            }
+
+            let mut parts = uri.clone().into_parts();
+            parts.path_and_query = Some(full
+                .parse()
+                .expect("replaced path should be valid"));
+            http::Uri::from_parts(parts)
+                .expect("replaced path should be a valid uri")
+        };
+        *self.req.uri_mut() = uri;
+    }
+
     pub(crate) fn query(&self) -> Option<&str> {
         self.req.uri().query()
     }
@@ -125,15 +194,119 @@ impl Route {
         self.segments_index = index;
     }
 
+    pub(crate) fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            body_limit: self.body_limit,
+            decode_body: self.decode_body,
+            params_len: self.params.len(),
+            segments_index: self.segments_index,
+        }
+    }
+
+    pub(crate) fn reset(&mut self, snapshot: Snapshot) {
+        self.reset_matched_path_index(snapshot.segments_index);
+        self.params.truncate(snapshot.params_len);
+        self.body_limit = snapshot.body_limit;
+        self.decode_body = snapshot.decode_body;
+    }
+
+    pub(crate) fn param(&self, name: &str) -> Option<&str> {
+        // Search from the end, so nested routers win.
+        self.params
+            .iter()
+            .rev()
+            .find(|&&(ref n, _, _)| &**n == name)
+            .map(|&(_, start, end)| &self.full_path()[start..end])
+    }
+
+    pub(crate) fn push_param(&mut self, name: Arc<str>, start: usize, end: usize) {
+        self.params.push((name, start, end));
+    }
+
     pub(crate) fn take_body(&mut self) -> Option<Body> {
         match self.body {
             BodyState::Ready => {
                 let body = mem::replace(self.req.body_mut(), Body::empty());
-                self.body = BodyState::Taken;
-                Some(body)
+                if self.track_body && !body.is_end_stream() {
+                    let done = Arc::new(AtomicBool::new(false));
+                    self.body = BodyState::Reading(done.clone());
+                    Some(Body::wrap_stream(TrackBody {
+                        body,
+                        done,
+                    }))
+                } else {
+                    self.body = BodyState::Taken;
+                    Some(body)
+                }
             },
-            BodyState::Taken => None,
+            BodyState::Taken |
+            BodyState::Reading(_) => None,
         }
     }
+
+    /// Limit how many bytes of the body may be read, once taken.
+    ///
+    /// If set more than once, the smallest limit wins.
+    pub(crate) fn limit_body(&mut self, limit: u64) {
+        self.body_limit = Some(self.body_limit.map_or(limit, |l| l.min(limit)));
+    }
+
+    pub(crate) fn body_limit(&self) -> Option<u64> {
+        self.body_limit
+    }
+
+
+    /// Decode the body by its `content-encoding`, once taken.
+    pub(crate) fn decode_body(&mut self) {
+        self.decode_body = true;
+    }
+
+    pub(crate) fn is_decoding_body(&self) -> bool {
+        self.decode_body
+    }
+
+    /// Keep track of whether the body is still being read, once taken.
+    ///
+    /// This costs a little, so is only done when something wants to know,
+    /// like a timeout.
+    pub(crate) fn track_body(&mut self) {
+        self.track_body = true;
+    }
+
+    pub(crate) fn is_reading_body(&self) -> bool {
+        match self.body {
+            BodyState::Reading(ref done) => !done.load(Ordering::Acquire),
+            BodyState::Ready |
+            BodyState::Taken => false,
+        }
+    }
+}
+
+struct TrackBody {
+    body: Body,
+    done: Arc<AtomicBool>,
+}
+
+impl Stream for TrackBody {
+    type Item = Chunk;
+    type Error = ::hyper::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let polled = self.body.poll();
+        match polled {
+            Ok(Async::Ready(Some(_))) |
+            Ok(Async::NotReady) => (),
+            Ok(Async::Ready(None)) |
+            Err(_) => self.done.store(true, Ordering::Release),
+        }
+        polled
+    }
+}
+
+// A handler may drop the body without reading all of it, which is also done.
+impl Drop for TrackBody {
+    fn drop(&mut self) {
+        self.done.store(true, Ordering::Release);
+    }
 }
 
@@ -0,0 +1,699 @@
+//! Route Introspection
+//!
+//! Every [`Filter`](::Filter) can [`describe`](::Filter::describe) what it
+//! matches, such as path segments, methods, and required headers. From
+//! that, [`list`](list) dumps a whole filter tree as a route listing, and
+//! [`shadowed`](shadowed) finds routes that can never be reached, because
+//! an earlier route matches every request they would. Routes can also be
+//! [`named`](named), so [`urls`](urls) can build their URLs.
+//!
+//! # Example
+//!
+//! ```
+//! use warp::Filter;
+//!
+//! let me = warp::path("users")
+//!     .and(warp::path::param::<String>())
+//!     .map(|name| format!("Hello, {}!", name));
+//! let unreachable = warp::path("users")
+//!     .and(warp::path("me"))
+//!     .map(|| "It's me!");
+//! let routes = me.or(unreachable);
+//!
+//! for route in warp::routes::list(&routes) {
+//!     println!("{}", route);
+//! }
+//!
+//! for shadowed in warp::routes::shadowed(&routes) {
+//!     println!("{}", shadowed);
+//! }
+//! # assert_eq!(warp::routes::shadowed(&routes).len(), 1);
+//! ```
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::fmt;
+use std::marker::PhantomData;
+
+use http::Method;
+
+use ::filter::{describe, Filter};
+use urlencoding;
+
+/// A description of what a `Filter` matches.
+///
+/// Built-in filters describe themselves. Filters that check requests in
+/// other ways, such as `and_then` or the `body` filters, are `Opaque`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Description {
+    /// Matches every request.
+    Any,
+    /// May reject requests in ways that can't be described.
+    Opaque,
+    /// Matches a literal path segment.
+    Path(String),
+    /// Matches a path segment that parses as the type with this `TypeId`.
+    Param(TypeId),
+    /// Matches any path segment, captured with this name by a template.
+    Capture(String),
+    /// Matches the rest of the path.
+    Tail,
+    /// Matches the end of the path.
+    End,
+    /// Matches a request method.
+    Method(Method),
+    /// Requires a header, with a specific value if set.
+    Header {
+        /// The name of the header.
+        name: String,
+        /// The value the header must have.
+        value: Option<String>,
+    },
+    /// Names the path matched so far, for [`urls`](urls).
+    Name(String),
+    /// Matches when all of these match, in order.
+    And(Vec<Description>),
+    /// Matches when any of these match, trying them in order.
+    Or(Vec<Description>),
+}
+
+impl Description {
+    pub(crate) fn param<T: 'static>() -> Description {
+        Description::Param(TypeId::of::<T>())
+    }
+
+    pub(crate) fn and(first: Description, second: Description) -> Description {
+        let mut all = match (first, second) {
+            (Description::Any, other) | (other, Description::Any) => return other,
+            (Description::Opaque, Description::Opaque) => return Description::Opaque,
+            (Description::And(mut first), Description::And(second)) => {
+                first.extend(second);
+                first
+            },
+            (Description::And(mut first), second) => {
+                first.push(second);
+                first
+            },
+            (first, Description::And(mut second)) => {
+                second.insert(0, first);
+                second
+            },
+            (first, second) => vec![first, second],
+        };
+        // Several opaque checks in a row are no more useful than one.
+        all.dedup_by(|a, b| *a == Description::Opaque && *b == Description::Opaque);
+        Description::And(all)
+    }
+
+    pub(crate) fn or(first: Description, second: Description) -> Description {
+        match (first, second) {
+            (Description::Or(mut first), Description::Or(second)) => {
+                first.extend(second);
+                Description::Or(first)
+            },
+            (Description::Or(mut first), second) => {
+                first.push(second);
+                Description::Or(first)
+            },
+            (first, Description::Or(mut second)) => {
+                second.insert(0, first);
+                Description::Or(second)
+            },
+            (first, second) => Description::Or(vec![first, second]),
+        }
+    }
+
+    // Every route through this description, as the list of constraints
+    // on each.
+    fn routes(&self) -> Vec<Vec<Description>> {
+        match *self {
+            Description::Any => vec![Vec::new()],
+            Description::And(ref all) => {
+                all.iter().fold(vec![Vec::new()], |routes, next| {
+                    let next = next.routes();
+                    let mut product = Vec::with_capacity(routes.len() * next.len());
+                    for route in &routes {
+                        for tail in &next {
+                            let mut route = route.clone();
+                            route.extend(tail.iter().cloned());
+                            product.push(route);
+                        }
+                    }
+                    product
+                })
+            },
+            Description::Or(ref any) => {
+                any.iter()
+                    .flat_map(Description::routes)
+                    .collect()
+            },
+            ref leaf => vec![vec![leaf.clone()]],
+        }
+    }
+}
+
+impl fmt::Display for Description {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Description::Any => f.write_str("any"),
+            Description::Opaque => f.write_str("custom"),
+            Description::Path(ref p) => write!(f, "/{}", p),
+            Description::Param(_) => f.write_str("/{param}"),
+            Description::Capture(ref name) => write!(f, "/{{{}}}", name),
+            Description::Tail => f.write_str("/*"),
+            Description::End => f.write_str("end"),
+            Description::Method(ref m) => fmt::Display::fmt(m, f),
+            Description::Header { ref name, value: Some(ref value) } => write!(f, "[{}: {}]", name, value),
+            Description::Header { ref name, value: None } => write!(f, "[{}]", name),
+            Description::Name(ref name) => write!(f, "name {:?}", name),
+            Description::And(ref all) => join(f, all, " and "),
+            Description::Or(ref any) => join(f, any, " or "),
+        }
+    }
+}
+
+fn join(f: &mut fmt::Formatter, descriptions: &[Description], sep: &str) -> fmt::Result {
+    f.write_str("(")?;
+    for (i, description) in descriptions.iter().enumerate() {
+        if i > 0 {
+            f.write_str(sep)?;
+        }
+        fmt::Display::fmt(description, f)?;
+    }
+    f.write_str(")")
+}
+
+/// Lists every route of a `Filter`, in the order they are tried.
+///
+/// Each `or` splits the listing into more routes.
+pub fn list<F: Filter>(filter: &F) -> Vec<Route> {
+    filter
+        .description()
+        .routes()
+        .into_iter()
+        .map(|constraints| Route {
+            constraints,
+        })
+        .collect()
+}
+
+/// Finds the routes of a `Filter` that are shadowed by an earlier route.
+///
+/// A route is shadowed if an earlier route is sure to match every request
+/// it would. Only routes with described constraints are considered, so
+/// shadowing by an `Opaque` route isn't reported. Each one is also logged
+/// as a warning.
+pub fn shadowed<F: Filter>(filter: &F) -> Vec<Shadowed> {
+    let routes = list(filter);
+    let mut found = Vec::new();
+    for (i, later) in routes.iter().enumerate() {
+        if let Some(earlier) = routes[..i].iter().find(|earlier| earlier.covers(later)) {
+            let shadowed = Shadowed {
+                earlier: earlier.clone(),
+                later: later.clone(),
+            };
+            warn!("{}", shadowed);
+            found.push(shadowed);
+        }
+    }
+    found
+}
+
+/// A single route of a `Filter`, as found by [`list`](list).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Route {
+    constraints: Vec<Description>,
+}
+
+#[derive(PartialEq)]
+enum PathEnd {
+    End,
+    Open,
+    Tail,
+}
+
+impl Route {
+    /// The constraints a request must meet to match this route, in order.
+    ///
+    /// These never contain `Any`, `And`, or `Or` descriptions.
+    pub fn constraints(&self) -> &[Description] {
+        &self.constraints
+    }
+
+    fn segments(&self) -> (Vec<&Description>, PathEnd) {
+        let mut segments = Vec::new();
+        for constraint in &self.constraints {
+            match *constraint {
+                Description::Path(_) |
+                Description::Param(_) |
+                Description::Capture(_) => segments.push(constraint),
+                Description::End => return (segments, PathEnd::End),
+                Description::Tail => return (segments, PathEnd::Tail),
+                _ => (),
+            }
+        }
+        (segments, PathEnd::Open)
+    }
+
+    // Whether this route matches every request `other` does.
+    fn covers(&self, other: &Route) -> bool {
+        if self.constraints.contains(&Description::Opaque) {
+            return false;
+        }
+
+        for constraint in &self.constraints {
+            match *constraint {
+                Description::Method(_) => {
+                    if !other.constraints.contains(constraint) {
+                        return false;
+                    }
+                },
+                Description::Header { ref name, ref value } => {
+                    let required = other.constraints.iter().any(|c| match *c {
+                        Description::Header { name: ref n, value: ref v } => {
+                            n.eq_ignore_ascii_case(name) && (value.is_none() || v == value)
+                        },
+                        _ => false,
+                    });
+                    if !required {
+                        return false;
+                    }
+                },
+                _ => (),
+            }
+        }
+
+        let (segments, end) = self.segments();
+        let (other_segments, other_end) = other.segments();
+        if other_segments.len() < segments.len() {
+            return false;
+        }
+        if end == PathEnd::End && (other_end != PathEnd::End || other_segments.len() != segments.len()) {
+            return false;
+        }
+        segments
+            .iter()
+            .zip(other_segments.iter())
+            .all(|(segment, other)| segment_covers(segment, other))
+    }
+}
+
+fn segment_covers(segment: &Description, other: &Description) -> bool {
+    match (segment, other) {
+        (&Description::Path(ref a), &Description::Path(ref b)) => a == b,
+        (&Description::Param(a), &Description::Param(b)) if a == b => true,
+        (&Description::Param(a), _) => a == TypeId::of::<String>(),
+        (&Description::Capture(_), _) => true,
+        _ => false,
+    }
+}
+
+impl fmt::Display for Route {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let methods = self.constraints
+            .iter()
+            .filter_map(|c| match *c {
+                Description::Method(ref m) => Some(m.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if !methods.is_empty() {
+            write!(f, "{} ", methods.join(","))?;
+        }
+
+        let (segments, end) = self.segments();
+        if segments.is_empty() && end != PathEnd::Tail {
+            f.write_str("/")?;
+        }
+        for segment in segments {
+            fmt::Display::fmt(segment, f)?;
+        }
+        match end {
+            PathEnd::End => (),
+            PathEnd::Open => f.write_str(" (prefix)")?,
+            PathEnd::Tail => f.write_str("/*")?,
+        }
+
+        for constraint in &self.constraints {
+            if let Description::Header { .. } = *constraint {
+                write!(f, " {}", constraint)?;
+            }
+        }
+        if self.constraints.contains(&Description::Opaque) {
+            f.write_str(" (custom)")?;
+        }
+        Ok(())
+    }
+}
+
+/// A route shadowed by an earlier route, as found by
+/// [`shadowed`](shadowed).
+#[derive(Clone, Debug)]
+pub struct Shadowed {
+    earlier: Route,
+    later: Route,
+}
+
+impl Shadowed {
+    /// The earlier route, matching every request of the later one.
+    pub fn earlier(&self) -> &Route {
+        &self.earlier
+    }
+
+    /// The later route, which can't be reached.
+    pub fn later(&self) -> &Route {
+        &self.later
+    }
+}
+
+impl fmt::Display for Shadowed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "route `{}` is shadowed by earlier route `{}`", self.later, self.earlier)
+    }
+}
+
+/// Names the routes through a `Filter`, so [`urls`](urls) can build their
+/// URL.
+///
+/// The URL is the path matched up to the end of this filter, including
+/// any prefix matched before it, such as the `users/{param}` of a
+/// `path!("users" / u32)`.
+///
+/// # Example
+///
+/// ```
+/// #[macro_use] extern crate warp;
+/// use warp::Filter;
+///
+/// # fn main() {
+/// let user = warp::routes::named("user_detail", path!("users" / u32))
+///     .map(|id| format!("user #{}", id));
+///
+/// let urls = warp::routes::urls(&user);
+/// let user_url = urls.get::<(u32,)>("user_detail").unwrap();
+/// assert_eq!(user_url.url((5,)).unwrap(), "/users/5");
+/// # }
+/// ```
+pub fn named<F>(name: &'static str, filter: F) -> impl Filter<Extract=F::Extract, Error=F::Error> + Clone
+where
+    F: Filter + Clone,
+{
+    let description = Description::and(filter.description(), Description::Name(name.to_owned()));
+    describe(filter, move || description.clone())
+}
+
+/// Finds the named routes of a `Filter`, to build their URLs.
+///
+/// Routes are named with [`named`](named), or with
+/// [`Router::name`](::router::Router::name).
+///
+/// # Panics
+///
+/// This panics if a name is used for different paths, so mistakes are
+/// caught at startup.
+pub fn urls<F: Filter>(filter: &F) -> Urls {
+    let mut paths: HashMap<String, Vec<Description>> = HashMap::new();
+    for route in filter.description().routes() {
+        let mut path = Vec::new();
+        for constraint in route {
+            match constraint {
+                Description::Path(_) |
+                Description::Param(_) |
+                Description::Capture(_) |
+                Description::Tail => path.push(constraint),
+                Description::Name(name) => {
+                    if let Some(existing) = paths.get(&name) {
+                        assert!(
+                            *existing == path,
+                            "route name {:?} is used for different paths: {} and {}",
+                            name,
+                            Path(existing),
+                            Path(&path),
+                        );
+                        continue;
+                    }
+                    paths.insert(name, path.clone());
+                },
+                _ => (),
+            }
+        }
+    }
+    Urls {
+        paths,
+    }
+}
+
+/// The named routes of a `Filter`, found by [`urls`](urls).
+#[derive(Clone, Debug)]
+pub struct Urls {
+    paths: HashMap<String, Vec<Description>>,
+}
+
+impl Urls {
+    /// Gets the route with this `name`, to build its URL from a tuple of
+    /// parameter values `P`, one for each parameter of its path, in order.
+    ///
+    /// Get every `UrlFor` at startup, so a route with a different number
+    /// of parameters is found right away, instead of when a URL is built.
+    ///
+    /// # Errors
+    ///
+    /// If there is no route with this `name`, or its path doesn't have as
+    /// many parameters as `P`, a [`UrlError`](UrlError) naming
+    /// the missing parameters, or counting the unexpected ones, is
+    /// returned.
+    pub fn get<P: UrlParams>(&self, name: &str) -> Result<UrlFor<P>, UrlError> {
+        let path = self.paths
+            .get(name)
+            .ok_or_else(|| UrlError::new(name, UrlErrorKind::UnknownRoute))?;
+
+        let params = path
+            .iter()
+            .filter_map(param_name)
+            .collect::<Vec<_>>();
+        if params.len() > P::LEN {
+            let missing = params[P::LEN..].to_vec();
+            return Err(UrlError::new(name, UrlErrorKind::MissingParams(missing)));
+        }
+        if params.len() < P::LEN {
+            let unexpected = P::LEN - params.len();
+            return Err(UrlError::new(name, UrlErrorKind::UnexpectedParams(unexpected)));
+        }
+
+        Ok(UrlFor {
+            name: name.to_owned(),
+            path: path.clone(),
+            _marker: PhantomData,
+        })
+    }
+}
+
+// The name of a path parameter, or `None` for a literal segment.
+fn param_name(segment: &Description) -> Option<String> {
+    match *segment {
+        Description::Param(_) => Some("param".to_owned()),
+        Description::Capture(ref name) => Some(name.clone()),
+        Description::Tail => Some("*".to_owned()),
+        _ => None,
+    }
+}
+
+// Displays a path, as the segments of a route.
+struct Path<'a>(&'a [Description]);
+
+impl<'a> fmt::Display for Path<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("/");
+        }
+        for segment in self.0 {
+            fmt::Display::fmt(segment, f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds the URL of a named route, from a tuple of parameter values `P`.
+///
+/// Created with [`Urls::get`](Urls::get).
+pub struct UrlFor<P> {
+    name: String,
+    path: Vec<Description>,
+    _marker: PhantomData<fn(P)>,
+}
+
+impl<P: UrlParams> UrlFor<P> {
+    /// Builds the URL, filling the parameters of the path with `params`.
+    ///
+    /// Each parameter is percent-encoded. A final tail, such as from
+    /// [`warp::path::tail`](::path::tail), keeps its `/`.
+    ///
+    /// # Errors
+    ///
+    /// If a parameter other than a tail is empty, a
+    /// [`UrlError`](UrlError) is returned, since the URL
+    /// wouldn't match the route.
+    pub fn url(&self, params: P) -> Result<String, UrlError> {
+        let values = params.values();
+        let mut values = values.iter();
+        let mut url = String::new();
+        for segment in &self.path {
+            if let Description::Path(ref s) = *segment {
+                url.push('/');
+                url.push_str(s);
+                continue;
+            }
+            let value = values
+                .next()
+                .expect("UrlFor has a value for each parameter")
+                .to_string();
+            match *segment {
+                Description::Tail => push_tail(&mut url, &value),
+                _ => {
+                    let param = param_name(segment).expect("segment is a parameter");
+                    push_param(&mut url, &self.name, &param, &value)?;
+                },
+            }
+        }
+
+        if url.is_empty() {
+            url.push('/');
+        }
+        Ok(url)
+    }
+}
+
+impl<P> Clone for UrlFor<P> {
+    fn clone(&self) -> UrlFor<P> {
+        UrlFor {
+            name: self.name.clone(),
+            path: self.path.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<P> fmt::Debug for UrlFor<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UrlFor")
+            .field("name", &self.name)
+            .field("path", &format_args!("{}", Path(&self.path)))
+            .finish()
+    }
+}
+
+// Appends a parameter value to a URL, as a single segment.
+fn push_param(url: &mut String, name: &str, param: &str, value: &str) -> Result<(), UrlError> {
+    if value.is_empty() {
+        return Err(UrlError::new(name, UrlErrorKind::EmptyParam(param.to_owned())));
+    }
+    url.push('/');
+    url.push_str(&encode_segment(value));
+    Ok(())
+}
+
+// Appends the rest of a path to a URL, keeping its `/`.
+fn push_tail(url: &mut String, value: &str) {
+    for seg in value.trim_left_matches('/').split('/') {
+        url.push('/');
+        url.push_str(&encode_segment(seg));
+    }
+}
+
+fn encode_segment(seg: &str) -> String {
+    match seg {
+        // Would otherwise be removed as dot segments.
+        "." => "%2E".to_owned(),
+        ".." => "%2E%2E".to_owned(),
+        seg => urlencoding::encode(seg),
+    }
+}
+
+/// An error finding a URL with [`Urls::get`](Urls::get), or building it
+/// with [`UrlFor::url`](UrlFor::url).
+#[derive(Debug)]
+pub struct UrlError {
+    kind: UrlErrorKind,
+    name: String,
+}
+
+#[derive(Debug)]
+enum UrlErrorKind {
+    EmptyParam(String),
+    // The parameters a `UrlFor` was created with too few values for.
+    MissingParams(Vec<String>),
+    // How many more values a `UrlFor` was created with than parameters.
+    UnexpectedParams(usize),
+    UnknownRoute,
+}
+
+impl UrlError {
+    fn new(name: &str, kind: UrlErrorKind) -> UrlError {
+        UrlError {
+            kind,
+            name: name.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for UrlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            UrlErrorKind::EmptyParam(ref param) => write!(f, "empty parameter `{}` for route {:?}", param, self.name),
+            UrlErrorKind::MissingParams(ref params) => write!(f, "missing parameters `{}` for route {:?}", params.join("`, `"), self.name),
+            UrlErrorKind::UnexpectedParams(n) => write!(f, "{} unexpected parameters for route {:?}", n, self.name),
+            UrlErrorKind::UnknownRoute => write!(f, "no route named {:?}", self.name),
+        }
+    }
+}
+
+impl StdError for UrlError {
+    fn description(&self) -> &str {
+        "error building route URL"
+    }
+}
+
+/// Parameter values to build a URL with a [`UrlFor`](UrlFor).
+///
+/// This is a tuple of `Display` values, such as `(u32,)` or
+/// `(&str, u32)`, up to 8 long, or `()` for a path without parameters.
+pub trait UrlParams: sealed::UrlParamsSealed {}
+
+impl<T: sealed::UrlParamsSealed> UrlParams for T {}
+
+macro_rules! url_params {
+    ($($len:expr => ($($T:ident),*),)*) => {$(
+        impl<$($T: fmt::Display),*> sealed::UrlParamsSealed for ($($T,)*) {
+            const LEN: usize = $len;
+
+            fn values(&self) -> Vec<&fmt::Display> {
+                #[allow(non_snake_case)]
+                let ($(ref $T,)*) = *self;
+                vec![$($T as &fmt::Display),*]
+            }
+        }
+    )*};
+}
+
+url_params! {
+    0 => (),
+    1 => (A),
+    2 => (A, B),
+    3 => (A, B, C),
+    4 => (A, B, C, D),
+    5 => (A, B, C, D, E),
+    6 => (A, B, C, D, E, F),
+    7 => (A, B, C, D, E, F, G),
+    8 => (A, B, C, D, E, F, G, H),
+}
+
+mod sealed {
+    use std::fmt;
+
+    pub trait UrlParamsSealed {
+        const LEN: usize;
+
+        fn values(&self) -> Vec<&fmt::Display>;
+    }
+}
@@ -1,14 +1,25 @@
-use std::net::SocketAddr;
-use std::sync::Arc;
+use std::net::{SocketAddr, TcpListener as StdTcpListener};
+#[cfg(unix)]
+use std::os::unix::io::RawFd;
+#[cfg(any(unix, feature = "tls"))]
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use futures::{Async, Future, Poll, Stream};
+use futures::{future, Async, Future, Poll, Stream};
+use futures::future::{Either, ExecuteError, Executor, Shared};
+use futures::sync::oneshot;
+use http::StatusCode;
 use hyper::{rt, Server as HyperServer};
-use hyper::service::{service_fn};
+use hyper::service::{make_service_fn, service_fn};
+use tokio::executor::DefaultExecutor;
+use tokio::timer::Delay;
 use tokio_io::{AsyncRead, AsyncWrite};
 
 use ::never::Never;
 use ::reject::Reject;
-use ::reply::{ReplySealed, Reply};
+use ::reply::{ReplySealed, Reply, Response};
+use ::transport::{AddrIncoming, Conn, ConnInfo, LiftIo, ManyIncoming, RequestGuard, Stats, Transport};
 use ::Request;
 
 /// Create a `Server` with the provided service.
@@ -17,16 +28,105 @@ where
     S: IntoWarpService + 'static,
 {
     Server {
+        head_timeout: None,
+        keep_alive: true,
+        limits: HeadLimits::default(),
+        max_buf_size: None,
+        nodelay: false,
         pipeline: false,
+        protocol: Protocol::Auto,
+        proxy_protocol: false,
+        request_timeout: None,
         service,
+        #[cfg(unix)]
+        unix_mode: None,
     }
 }
 
 /// A Warp Server ready to filter requests.
+///
+/// # HTTP/2
+///
+/// By default, a `Server` speaks both HTTP/1 and HTTP/2. Cleartext HTTP/2
+/// clients using "prior knowledge" (h2c) are detected from the connection
+/// preface, and TLS servers advertise `h2` and `http/1.1` with ALPN. Use
+/// [`http2_only`](Server::http2_only) to refuse HTTP/1 connections.
+///
+/// # Connection Tuning
+///
+/// Connections can be tuned with builder methods, before binding:
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use warp::Filter;
+///
+/// let routes = warp::any()
+///     .map(|| "Hello, World!");
+///
+/// warp::serve(routes)
+///     .header_read_timeout(Duration::from_secs(5))
+///     .max_headers(50)
+///     .max_header_size(16 * 1024)
+///     .tcp_nodelay(true)
+///     .run(([127, 0, 0, 1], 3030));
+/// ```
 #[derive(Debug)]
 pub struct Server<S> {
+    head_timeout: Option<Duration>,
+    keep_alive: bool,
+    limits: HeadLimits,
+    max_buf_size: Option<usize>,
+    nodelay: bool,
     pipeline: bool,
+    protocol: Protocol,
+    proxy_protocol: bool,
+    request_timeout: Option<Duration>,
     service: S,
+    #[cfg(unix)]
+    unix_mode: Option<u32>,
+}
+
+/// The HTTP versions a `Server` will speak.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Protocol {
+    Auto,
+    Http1,
+    Http2,
+}
+
+/// Limits on request heads, checked before any filters run.
+#[derive(Clone, Copy, Debug, Default)]
+struct HeadLimits {
+    headers: Option<usize>,
+    size: Option<usize>,
+}
+
+impl HeadLimits {
+    fn check(&self, req: &Request) -> Result<(), Response> {
+        let headers = req.headers();
+
+        let too_many = self.headers
+            .map(|max| headers.len() > max)
+            .unwrap_or(false);
+        let too_large = self.size
+            .map(|max| {
+                let size = headers
+                    .iter()
+                    .map(|(name, value)| name.as_str().len() + value.len())
+                    .sum::<usize>();
+                size > max
+            })
+            .unwrap_or(false);
+
+        if too_many || too_large {
+            debug!("request headers exceed limits, too many={}, too large={}", too_many, too_large);
+            let mut res = Response::default();
+            *res.status_mut() = StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE;
+            Err(res)
+        } else {
+            Ok(())
+        }
+    }
 }
 
 // Getting all various generic bounds to make this a re-usable method is
@@ -34,23 +134,62 @@ pub struct Server<S> {
 macro_rules! into_service {
     ($this:ident) => ({
         let inner = Arc::new($this.service.into_warp_service());
-        move || {
+        let limits = $this.limits;
+        let timeout = $this.request_timeout;
+        make_service_fn(move |conn| {
             let inner = inner.clone();
-            service_fn(move |req| {
-                ReplyFuture {
-                    inner: inner.call(req)
+            let conn_info = Transport::conn_info(conn);
+            let requests = Conn::requests(conn).clone();
+            Ok::<_, Never>(service_fn(move |req| {
+                let guard = requests.start();
+                if let Err(res) = limits.check(&req) {
+                    return Either::A(future::ok(res));
                 }
-            })
+                Either::B(ReplyFuture {
+                    inner: inner.call(req, conn_info, timeout),
+                    _guard: guard,
+                })
+            }))
+        })
+    });
+}
+// Applies the configured protocol options to a hyper server `Builder`.
+macro_rules! configure {
+    ($this:ident, $builder:expr) => ({
+        let builder = $builder
+            .http1_keepalive($this.keep_alive)
+            .http1_pipeline_flush($this.pipeline);
+        let builder = match $this.protocol {
+            Protocol::Auto => builder,
+            Protocol::Http1 => builder.http1_only(true),
+            Protocol::Http2 => builder.http2_only(true),
+        };
+        match $this.max_buf_size {
+            Some(max) => builder.http1_max_buf_size(max),
+            None => builder,
         }
     });
 }
-macro_rules! bind_inner {
-    ($this:ident, $addr:expr) => ({
+// Serves a stream of `Transport`s, returning a hyper `Server`.
+macro_rules! serve {
+    ($this:ident, $incoming:expr) => (
+        serve!($this, $incoming, Arc::default(), Exec::default())
+    );
+    ($this:ident, $incoming:expr, $stats:expr, $exec:expr) => ({
+        let head_timeout = $this.head_timeout;
+        let stats: Arc<Stats> = $stats;
+        let incoming = $incoming.map(move |io| Conn::new(io, head_timeout, stats.clone()));
         let service = into_service!($this);
-        let srv = HyperServer::bind(&$addr.into())
-            .http1_pipeline_flush($this.pipeline)
-            .serve(service);
-        let addr = srv.local_addr();
+        configure!($this, HyperServer::builder(incoming))
+            .executor($exec)
+            .serve(service)
+    });
+}
+macro_rules! bind_inner {
+    ($this:ident, $incoming:expr) => ({
+        let incoming: AddrIncoming = $incoming;
+        let addr = incoming.local_addr();
+        let srv = serve!($this, incoming);
         (addr, srv)
     });
 }
@@ -99,7 +238,7 @@ where
     /// Returns the bound address and a `Future` that can be executed on
     /// any runtime.
     pub fn bind_ephemeral(self, addr: impl Into<SocketAddr> + 'static) -> (SocketAddr, impl Future<Item=(), Error=()> + 'static) {
-        let (addr, srv) = bind_inner!(self, addr);
+        let (addr, srv) = bind_inner!(self, bind_incoming(addr.into(), &self));
         (addr, srv.map_err(|e| error!("server error: {}", e)))
     }
 
@@ -141,13 +280,232 @@ where
         addr: impl Into<SocketAddr> + 'static,
         signal: impl Future<Item=()> + Send + 'static,
     ) -> (SocketAddr, impl Future<Item=(), Error=()> + 'static) {
-        let (addr, srv) = bind_inner!(self, addr);
+        let (addr, srv) = bind_inner!(self, bind_incoming(addr.into(), &self));
         let fut = srv
             .with_graceful_shutdown(signal)
             .map_err(|e| error!("server error: {}", e));
         (addr, fut)
     }
 
+    /// Bind to several socket addresses at once.
+    ///
+    /// All of the listeners are served by the same filters, and the
+    /// extracted [`warp::addr::listener`](::addr::listener) tells which
+    /// listener a request arrived on.
+    ///
+    /// Returns the bound addresses, in the same order as `addrs`, and a
+    /// `Future` that can be executed on any runtime.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `addrs` is empty, or if any of them cannot be bound.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::net::SocketAddr;
+    /// use warp::Filter;
+    ///
+    /// let routes = warp::any()
+    ///     .map(|| "Hello, World!");
+    ///
+    /// let addrs: Vec<SocketAddr> = vec![
+    ///     ([0, 0, 0, 0], 3030).into(),
+    ///     ([0, 0, 0, 0, 0, 0, 0, 0], 3030).into(),
+    /// ];
+    ///
+    /// let (_addrs, server) = warp::serve(routes)
+    ///     .bind_many(addrs);
+    ///
+    /// warp::spawn(server);
+    /// ```
+    pub fn bind_many<A>(self, addrs: impl IntoIterator<Item=A>) -> (Vec<SocketAddr>, impl Future<Item=(), Error=()> + 'static)
+    where
+        A: Into<SocketAddr>,
+    {
+        let (addrs, incoming) = many_incoming(addrs, &self);
+        let fut = serve!(self, incoming)
+            .map_err(|e| error!("server error: {}", e));
+        (addrs, fut)
+    }
+
+    /// Bind to several socket addresses at once, with one graceful shutdown
+    /// signal for all of them.
+    ///
+    /// When the signal completes, all of the listeners start the graceful
+    /// shutdown process. See [`bind_many`](Server::bind_many) for details.
+    pub fn bind_many_with_graceful_shutdown<A>(
+        self,
+        addrs: impl IntoIterator<Item=A>,
+        signal: impl Future<Item=()> + Send + 'static,
+    ) -> (Vec<SocketAddr>, impl Future<Item=(), Error=()> + 'static)
+    where
+        A: Into<SocketAddr>,
+    {
+        let (addrs, incoming) = many_incoming(addrs, &self);
+        let fut = serve!(self, incoming)
+            .with_graceful_shutdown(signal)
+            .map_err(|e| error!("server error: {}", e));
+        (addrs, fut)
+    }
+
+    /// Run this `Server` forever on the current thread, accepting
+    /// connections on an inherited listening socket.
+    ///
+    /// The `Server` takes ownership of the file descriptor `fd`, which must
+    /// be a TCP socket that is bound and listening, such as one passed from
+    /// a parent process or by a service manager. See
+    /// [`listen_fds`](::listen_fds) to find the sockets passed by systemd
+    /// socket activation.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `fd` isn't a TCP socket.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use warp::Filter;
+    ///
+    /// let routes = warp::any()
+    ///     .map(|| "Hello, World!");
+    ///
+    /// // The first socket passed by systemd...
+    /// warp::serve(routes)
+    ///     .run_from_fd(3);
+    /// ```
+    #[cfg(unix)]
+    pub fn run_from_fd(self, fd: RawFd) {
+        let listener = fd::listener(fd)
+            .unwrap_or_else(|e| panic!("error using fd {} as a listener: {}", fd, e));
+        let (addr, fut) = self.bind_listener(listener);
+
+        info!("warp drive engaged: listening on {} (fd {})", addr, fd);
+
+        rt::run(fut);
+    }
+
+    /// Accept connections on an already bound `std::net::TcpListener`.
+    ///
+    /// This is useful for listeners inherited from a parent process, or set
+    /// up with socket options warp doesn't know about.
+    ///
+    /// Returns the address of the listener and a `Future` that can be
+    /// executed on any runtime.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the listener can't be registered with the reactor.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::net::TcpListener;
+    /// use warp::Filter;
+    ///
+    /// let routes = warp::any()
+    ///     .map(|| "Hello, World!");
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:3030").unwrap();
+    ///
+    /// let (_addr, server) = warp::serve(routes)
+    ///     .bind_listener(listener);
+    ///
+    /// warp::spawn(server);
+    /// ```
+    pub fn bind_listener(self, listener: StdTcpListener) -> (SocketAddr, impl Future<Item=(), Error=()> + 'static) {
+        let mut incoming = AddrIncoming::from_std(listener)
+            .unwrap_or_else(|e| panic!("error using listener: {}", e));
+        configure_incoming(&mut incoming, &self);
+        let (addr, srv) = bind_inner!(self, incoming);
+        (addr, srv.map_err(|e| error!("server error: {}", e)))
+    }
+
+    /// Bind to a possibly ephemeral socket address, returning a
+    /// [`ServerHandle`](ServerHandle) to control the server with.
+    ///
+    /// Returns the handle and a `Future` that can be executed on any
+    /// runtime. The `Future` completes once the server has been shut down
+    /// with the handle, and all of its connections are closed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use warp::Filter;
+    ///
+    /// let routes = warp::any()
+    ///     .map(|| "Hello, World!");
+    ///
+    /// let (handle, server) = warp::serve(routes)
+    ///     .bind_with_handle(([127, 0, 0, 1], 3030));
+    ///
+    /// warp::spawn(server);
+    ///
+    /// // Later, give in-flight requests 30 seconds to finish...
+    /// handle.shutdown_timeout(Duration::from_secs(30));
+    /// ```
+    pub fn bind_with_handle(
+        self,
+        addr: impl Into<SocketAddr> + 'static,
+    ) -> (ServerHandle, impl Future<Item=(), Error=()> + 'static) {
+        let incoming = bind_incoming(addr.into(), &self);
+        let addr = incoming.local_addr();
+
+        let (signal_tx, signal_rx) = oneshot::channel::<Option<Duration>>();
+        let (kill_tx, kill_rx) = oneshot::channel();
+        let stats = Arc::new(Stats::default());
+        let exec = Exec {
+            kill: Some(kill_rx.shared()),
+        };
+
+        // Once signaled, start the graceful shutdown, and possibly a timer
+        // to force close the connections still open when it fires.
+        let (timeout_tx, timeout_rx) = oneshot::channel::<Duration>();
+        let signal = signal_rx.then(move |res| match res {
+            Ok(timeout) => {
+                if let Some(timeout) = timeout {
+                    let _ = timeout_tx.send(timeout);
+                }
+                Either::A(future::ok::<(), ()>(()))
+            },
+            // All handles were dropped, so nothing can shut down the server.
+            Err(_canceled) => Either::B(future::empty()),
+        });
+        let kill = timeout_rx.then(move |res| match res {
+            Ok(timeout) => Either::A(Delay::new(Instant::now() + timeout).then(move |_| {
+                debug!("graceful shutdown timed out, closing connections");
+                let _ = kill_tx.send(());
+                Ok(())
+            })),
+            // Shut down without a timeout.
+            Err(_canceled) => Either::B(future::empty::<(), ()>()),
+        });
+
+        // The timer is dropped if the connections drain before it fires,
+        // so it doesn't keep the runtime alive.
+        let fut = serve!(self, incoming, stats.clone(), exec)
+            .with_graceful_shutdown(signal)
+            .map_err(|e| error!("server error: {}", e))
+            .select2(kill)
+            .then(|res| match res {
+                Ok(Either::A(((), _kill))) => Either::A(future::ok(())),
+                Err(Either::A(((), _kill))) => Either::A(future::err(())),
+                // Killed, so wait for the connections to close.
+                Ok(Either::B(((), server))) |
+                Err(Either::B(((), server))) => Either::B(server),
+            });
+
+        let handle = ServerHandle {
+            addr,
+            inner: Arc::new(HandleInner {
+                signal: Mutex::new(Some(signal_tx)),
+                stats,
+            }),
+        };
+        (handle, fut)
+    }
+
 
     /// Setup this `Server` with a specific stream of incoming connections.
     ///
@@ -160,13 +518,258 @@ where
         I::Item: AsyncRead + AsyncWrite + Send + 'static,
         I::Error: Into<Box<::std::error::Error + Send + Sync>>,
     {
-        let service = into_service!(self);
-        HyperServer::builder(incoming)
-            .http1_pipeline_flush(self.pipeline)
-            .serve(service)
+        serve!(self, incoming.map(LiftIo))
             .map_err(|e| error!("server error: {}", e))
     }
 
+    /// Run this `Server` forever on the current thread, listening on a Unix
+    /// Domain Socket at `path`.
+    ///
+    /// See [`bind_unix`](Server::bind_unix) for details.
+    #[cfg(unix)]
+    pub fn run_unix(self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        let fut = self.bind_unix(path);
+
+        info!("warp drive engaged: listening on unix:{}", path.display());
+
+        rt::run(fut);
+    }
+
+    /// Bind to a Unix Domain Socket at `path`, returning a `Future` that can
+    /// be executed on any runtime.
+    ///
+    /// If a socket file already exists at `path`, but nothing is listening
+    /// on it anymore, it's removed first. Any other existing file is left
+    /// alone, and binding fails.
+    ///
+    /// The credentials of connected peers are available to filters with
+    /// [`warp::unix::peer_cred`](::unix::peer_cred).
+    ///
+    /// # Panics
+    ///
+    /// This panics if the socket cannot be bound, or its permissions cannot
+    /// be set.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use warp::Filter;
+    ///
+    /// let routes = warp::any()
+    ///     .map(|| "Hello, Unix!");
+    ///
+    /// let server = warp::serve(routes)
+    ///     .unix_socket_mode(0o660)
+    ///     .bind_unix("/tmp/warp.sock");
+    ///
+    /// warp::spawn(server);
+    /// ```
+    #[cfg(unix)]
+    pub fn bind_unix(self, path: impl AsRef<Path>) -> impl Future<Item=(), Error=()> + 'static {
+        let incoming = unix::bind(path.as_ref(), self.unix_mode)
+            .incoming();
+        serve!(self, incoming)
+            .map_err(|e| error!("server error: {}", e))
+    }
+
+    /// Sets the file permissions of Unix Domain Sockets created by
+    /// [`bind_unix`](Server::bind_unix), such as `0o660`.
+    ///
+    /// If not set, the permissions are determined by the process umask.
+    #[cfg(unix)]
+    pub fn unix_socket_mode(mut self, mode: u32) -> Self {
+        self.unix_mode = Some(mode);
+        self
+    }
+
+    /// Sets whether to only accept HTTP/2 connections.
+    ///
+    /// When enabled, cleartext connections must start with the HTTP/2
+    /// preface ("prior knowledge"), and TLS servers only advertise `h2`
+    /// during ALPN negotiation.
+    ///
+    /// Default is `false`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use warp::Filter;
+    ///
+    /// let routes = warp::any()
+    ///     .map(|| "Hello, HTTP/2!");
+    ///
+    /// warp::serve(routes)
+    ///     .http2_only(true)
+    ///     .run(([127, 0, 0, 1], 3030));
+    /// ```
+    pub fn http2_only(mut self, enabled: bool) -> Self {
+        self.protocol = match (enabled, self.protocol) {
+            (true, _) => Protocol::Http2,
+            (false, Protocol::Http2) => Protocol::Auto,
+            (false, protocol) => protocol,
+        };
+        self
+    }
+
+    /// Sets whether to only accept HTTP/1 connections.
+    ///
+    /// When enabled, the HTTP/2 preface is not detected, and TLS servers
+    /// only advertise `http/1.1` during ALPN negotiation.
+    ///
+    /// Default is `false`.
+    pub fn http1_only(mut self, enabled: bool) -> Self {
+        self.protocol = match (enabled, self.protocol) {
+            (true, _) => Protocol::Http1,
+            (false, Protocol::Http1) => Protocol::Auto,
+            (false, protocol) => protocol,
+        };
+        self
+    }
+
+    /// Sets whether HTTP/1 connections are kept alive between requests.
+    ///
+    /// When disabled, connections are closed after each response.
+    ///
+    /// Default is `true`.
+    pub fn keep_alive(mut self, enabled: bool) -> Self {
+        self.keep_alive = enabled;
+        self
+    }
+
+    /// Sets how long a client may take to send the head of a request.
+    ///
+    /// The timer starts when a connection is accepted, and again when the
+    /// first bytes of each following request arrive. If the request line
+    /// and headers haven't been received when it fires, the connection is
+    /// closed. HTTP/2 connections are only limited until their preface is
+    /// received.
+    ///
+    /// Default is no timeout.
+    pub fn header_read_timeout(mut self, timeout: Duration) -> Self {
+        self.head_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets a default timeout for handling each request.
+    ///
+    /// If the filters haven't produced a reply within the `timeout`, the
+    /// request is rejected as if the whole filter chain was wrapped with
+    /// [`Filter::timeout`](::Filter::timeout).
+    ///
+    /// Default is no timeout.
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum number of headers a request may have.
+    ///
+    /// Requests with more headers are answered with
+    /// `431 Request Header Fields Too Large`, without running any filters.
+    /// Independently of this, hyper refuses requests with more than 100
+    /// headers.
+    ///
+    /// Default is no limit.
+    pub fn max_headers(mut self, max: usize) -> Self {
+        self.limits.headers = Some(max);
+        self
+    }
+
+    /// Sets the maximum combined size, in bytes, of the header names and
+    /// values of a request.
+    ///
+    /// Requests with larger headers are answered with
+    /// `431 Request Header Fields Too Large`, without running any filters.
+    ///
+    /// Default is no limit, other than [`max_buf_size`](Server::max_buf_size).
+    pub fn max_header_size(mut self, max: usize) -> Self {
+        self.limits.size = Some(max);
+        self
+    }
+
+    /// Sets the maximum size of the buffer used to read HTTP/1 connections.
+    ///
+    /// A request head must fit in this buffer, otherwise it's answered with
+    /// `431 Request Header Fields Too Large`.
+    ///
+    /// Default is hyper's default of about 400kb.
+    ///
+    /// # Panics
+    ///
+    /// Binding the server panics if this is less than 8192.
+    pub fn max_buf_size(mut self, max: usize) -> Self {
+        self.max_buf_size = Some(max);
+        self
+    }
+
+    /// Sets whether accepted connections start with a PROXY protocol header.
+    ///
+    /// Load balancers such as HAProxy or AWS NLBs can send the address of
+    /// the original client in a header, before any HTTP bytes. When
+    /// enabled, both the text (version 1) and binary (version 2) headers
+    /// are parsed, and the source and destination addresses they carry are
+    /// what [`warp::addr::remote`](::addr::remote) and
+    /// [`warp::addr::local`](::addr::local), as well as the
+    /// [`log`](::log()) filter, report.
+    ///
+    /// Connections that don't start with a valid header are closed. Only
+    /// enable this when all clients connect through a proxy sending one,
+    /// since anyone able to connect directly could claim any address. The
+    /// header must be received within the
+    /// [`header_read_timeout`](Server::header_read_timeout), if set.
+    ///
+    /// This only applies to sockets bound by the `Server`, not to streams
+    /// passed to [`serve_incoming`](Server::serve_incoming).
+    ///
+    /// Default is `false`.
+    pub fn proxy_protocol(mut self, enabled: bool) -> Self {
+        self.proxy_protocol = enabled;
+        self
+    }
+
+    /// Sets the `TCP_NODELAY` option on accepted connections.
+    ///
+    /// This only applies to sockets bound by the `Server`, not to streams
+    /// passed to [`serve_incoming`](Server::serve_incoming).
+    ///
+    /// Default is `false`.
+    pub fn tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.nodelay = enabled;
+        self
+    }
+
+    /// Configure a server to use TLS with the supplied certificate and key files.
+    ///
+    /// *This function requires the `"tls"` feature.*
+    ///
+    /// # Panics
+    ///
+    /// This panics if the certificate or key files cannot be read, or do not
+    /// contain a valid PEM encoded certificate chain and private key.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use warp::Filter;
+    ///
+    /// let routes = warp::any()
+    ///     .map(|| "Hello, TLS!");
+    ///
+    /// warp::serve(routes)
+    ///     .tls("examples/tls/cert.pem", "examples/tls/key.rsa")
+    ///     .run(([127, 0, 0, 1], 3030));
+    /// ```
+    #[cfg(feature = "tls")]
+    pub fn tls(self, cert: impl AsRef<Path>, key: impl AsRef<Path>) -> TlsServer<S> {
+        let tls = ::tls::configure(cert.as_ref(), key.as_ref());
+
+        TlsServer {
+            server: self,
+            tls,
+        }
+    }
+
     // Generally shouldn't be used, as it can slow down non-pipelined responses.
     //
     // It's only real use is to make silly pipeline benchmarks look better.
@@ -177,6 +780,408 @@ where
     }
 }
 
+/// A Warp Server ready to filter requests over TLS.
+///
+/// *This type requires the `"tls"` feature.*
+#[cfg(feature = "tls")]
+pub struct TlsServer<S> {
+    server: Server<S>,
+    tls: ::tokio_rustls::rustls::ServerConfig,
+}
+
+#[cfg(feature = "tls")]
+impl<S> TlsServer<S>
+where
+    S: IntoWarpService + 'static,
+    <<S::Service as WarpService>::Reply as Future>::Item: Reply + Send,
+    <<S::Service as WarpService>::Reply as Future>::Error: Reject + Send,
+{
+    /// Run this `TlsServer` forever on the current thread.
+    ///
+    /// *This function requires the `"tls"` feature.*
+    pub fn run(self, addr: impl Into<SocketAddr> + 'static) {
+        let (addr, fut) = self.bind_ephemeral(addr);
+
+        info!("warp drive engaged: listening on https://{}", addr);
+
+        rt::run(fut);
+    }
+
+    /// Bind to a socket address, returning a `Future` that can be
+    /// executed on any runtime.
+    ///
+    /// *This function requires the `"tls"` feature.*
+    pub fn bind(self, addr: impl Into<SocketAddr> + 'static) -> impl Future<Item=(), Error=()> + 'static {
+        let (_, fut) = self.bind_ephemeral(addr);
+        fut
+    }
+
+    /// Bind to a possibly ephemeral socket address.
+    ///
+    /// Returns the bound address and a `Future` that can be executed on
+    /// any runtime.
+    ///
+    /// *This function requires the `"tls"` feature.*
+    pub fn bind_ephemeral(self, addr: impl Into<SocketAddr> + 'static) -> (SocketAddr, impl Future<Item=(), Error=()> + 'static) {
+        let (addr, incoming) = tls_incoming(addr.into(), self.tls, &self.server);
+        let server = self.server;
+        let fut = serve!(server, incoming)
+            .map_err(|e| error!("server error: {}", e));
+        (addr, fut)
+    }
+
+    /// Create a server with graceful shutdown signal.
+    ///
+    /// When the signal completes, the server will start the graceful shutdown
+    /// process.
+    ///
+    /// *This function requires the `"tls"` feature.*
+    pub fn bind_with_graceful_shutdown(
+        self,
+        addr: impl Into<SocketAddr> + 'static,
+        signal: impl Future<Item=()> + Send + 'static,
+    ) -> (SocketAddr, impl Future<Item=(), Error=()> + 'static) {
+        let (addr, incoming) = tls_incoming(addr.into(), self.tls, &self.server);
+        let server = self.server;
+        let fut = serve!(server, incoming)
+            .with_graceful_shutdown(signal)
+            .map_err(|e| error!("server error: {}", e));
+        (addr, fut)
+    }
+}
+
+/// A handle to a running [`Server`](Server).
+///
+/// Returned by [`Server::bind_with_handle`](Server::bind_with_handle), it
+/// can be cloned and used from any thread to shut the server down, or to
+/// peek at how busy it is.
+#[derive(Clone, Debug)]
+pub struct ServerHandle {
+    addr: SocketAddr,
+    inner: Arc<HandleInner>,
+}
+
+#[derive(Debug)]
+struct HandleInner {
+    signal: Mutex<Option<oneshot::Sender<Option<Duration>>>>,
+    stats: Arc<Stats>,
+}
+
+impl ServerHandle {
+    /// Returns the local address the server is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Starts a graceful shutdown of the server.
+    ///
+    /// The server stops accepting new connections, and closes each open
+    /// connection once its in-flight requests have been answered.
+    ///
+    /// Only the first call to `shutdown` or `shutdown_timeout` has any
+    /// effect.
+    pub fn shutdown(&self) {
+        self.signal(None);
+    }
+
+    /// Starts a graceful shutdown of the server, with a deadline.
+    ///
+    /// This is like [`shutdown`](ServerHandle::shutdown), but connections
+    /// that are still open once the `timeout` has passed are closed, even
+    /// if their requests are still in flight.
+    pub fn shutdown_timeout(&self, timeout: Duration) {
+        self.signal(Some(timeout));
+    }
+
+    /// Returns the number of currently open connections.
+    pub fn open_connections(&self) -> usize {
+        self.inner.stats.connections()
+    }
+
+    /// Returns the number of requests currently being handled.
+    ///
+    /// A request is in flight from when its head has been received, until
+    /// the filters have produced a reply, or the request was dropped.
+    pub fn in_flight_requests(&self) -> usize {
+        self.inner.stats.in_flight()
+    }
+
+    fn signal(&self, timeout: Option<Duration>) {
+        let tx = self.inner.signal
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take();
+        if let Some(tx) = tx {
+            debug!("graceful shutdown started, timeout = {:?}", timeout);
+            let _ = tx.send(timeout);
+        }
+    }
+}
+
+/// Spawns the connection tasks of hyper onto the default executor, so that
+/// they can be dropped, closing them, when the `kill` signal fires.
+#[derive(Clone, Debug, Default)]
+struct Exec {
+    kill: Option<Shared<oneshot::Receiver<()>>>,
+}
+
+impl<F> Executor<F> for Exec
+where
+    F: Future<Item=(), Error=()> + Send + 'static,
+{
+    fn execute(&self, future: F) -> Result<(), ExecuteError<F>> {
+        let killable = Killable {
+            future,
+            kill: self.kill.clone(),
+        };
+        DefaultExecutor::current()
+            .execute(killable)
+            .map_err(|err| ExecuteError::new(err.kind(), err.into_future().future))
+    }
+}
+
+#[derive(Debug)]
+struct Killable<F> {
+    future: F,
+    kill: Option<Shared<oneshot::Receiver<()>>>,
+}
+
+impl<F> Future for Killable<F>
+where
+    F: Future<Item=(), Error=()>,
+{
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        let killed = match self.kill {
+            Some(ref mut kill) => match kill.poll() {
+                Ok(Async::Ready(_)) => true,
+                Ok(Async::NotReady) => false,
+                // The sender was dropped without a deadline being reached.
+                Err(_canceled) => {
+                    self.kill = None;
+                    false
+                }
+            },
+            None => false,
+        };
+
+        if killed {
+            trace!("connection task killed");
+            return Ok(Async::Ready(()));
+        }
+        self.future.poll()
+    }
+}
+
+#[cfg(feature = "tls")]
+impl<S> ::std::fmt::Debug for TlsServer<S>
+where
+    S: ::std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.debug_struct("TlsServer")
+            .field("server", &self.server)
+            .finish()
+    }
+}
+
+fn configure_incoming<S>(incoming: &mut AddrIncoming, server: &Server<S>) {
+    incoming.set_nodelay(server.nodelay);
+    if server.proxy_protocol {
+        incoming.set_proxy_protocol(server.head_timeout);
+    }
+}
+
+fn bind_incoming<S>(addr: SocketAddr, server: &Server<S>) -> AddrIncoming {
+    let mut incoming = AddrIncoming::bind(&addr)
+        .unwrap_or_else(|e| panic!("error binding to {}: {}", addr, e));
+    configure_incoming(&mut incoming, server);
+    incoming
+}
+
+fn many_incoming<A, S>(addrs: impl IntoIterator<Item=A>, server: &Server<S>) -> (Vec<SocketAddr>, ManyIncoming)
+where
+    A: Into<SocketAddr>,
+{
+    let incomings = addrs
+        .into_iter()
+        .map(|addr| bind_incoming(addr.into(), server))
+        .collect::<Vec<_>>();
+    assert!(!incomings.is_empty(), "bind_many requires at least one address");
+    let addrs = incomings
+        .iter()
+        .map(AddrIncoming::local_addr)
+        .collect();
+    (addrs, ManyIncoming::new(incomings))
+}
+
+#[cfg(feature = "tls")]
+fn tls_incoming<S>(
+    addr: SocketAddr,
+    mut tls: ::tokio_rustls::rustls::ServerConfig,
+    server: &Server<S>,
+) -> (SocketAddr, ::tls::TlsAcceptor) {
+    tls.set_protocols(&::tls::alpn_protocols(server.protocol));
+    let incoming = bind_incoming(addr, server);
+    let addr = incoming.local_addr();
+    (addr, ::tls::TlsAcceptor::new(tls, incoming))
+}
+
+/// Takes the listening sockets passed to this process by systemd socket
+/// activation.
+///
+/// The sockets are found with the `LISTEN_PID` and `LISTEN_FDS`
+/// environment variables, in the order they were configured. If the
+/// variables are missing, or meant for another process, no sockets are
+/// returned. The variables are removed, so that child processes don't also
+/// try to use the sockets.
+///
+/// This returns an error if any of the passed sockets isn't a TCP socket.
+///
+/// # Example
+///
+/// ```no_run
+/// use warp::Filter;
+///
+/// let routes = warp::any()
+///     .map(|| "Hello, World!");
+///
+/// let listener = warp::listen_fds()
+///     .expect("socket activation")
+///     .pop()
+///     .expect("no socket passed");
+///
+/// let (_addr, server) = warp::serve(routes)
+///     .bind_listener(listener);
+///
+/// warp::spawn(server);
+/// ```
+#[cfg(unix)]
+pub fn listen_fds() -> ::std::io::Result<Vec<StdTcpListener>> {
+    fd::listen_fds()
+}
+
+#[cfg(unix)]
+mod fd {
+    use std::env;
+    use std::io;
+    use std::mem;
+    use std::net::TcpListener;
+    use std::os::unix::io::{FromRawFd, RawFd};
+
+    use libc::{self, c_int, c_void, socklen_t};
+
+    // The first file descriptor passed by systemd.
+    const LISTEN_FDS_START: RawFd = 3;
+
+    pub(super) fn listen_fds() -> io::Result<Vec<TcpListener>> {
+        let pid = env::var("LISTEN_PID")
+            .ok()
+            .and_then(|pid| pid.parse::<libc::pid_t>().ok());
+        let fds = env::var("LISTEN_FDS")
+            .ok()
+            .and_then(|fds| fds.parse::<RawFd>().ok());
+
+        env::remove_var("LISTEN_PID");
+        env::remove_var("LISTEN_FDS");
+        env::remove_var("LISTEN_FDNAMES");
+
+        let fds = match (pid, fds) {
+            (Some(pid), Some(fds)) if pid == unsafe { libc::getpid() } => fds,
+            _ => return Ok(Vec::new()),
+        };
+
+        debug!("socket activation, LISTEN_FDS={}", fds);
+
+        (LISTEN_FDS_START..LISTEN_FDS_START + fds)
+            .map(|fd| {
+                // Don't leak the sockets to child processes.
+                if unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                listener(fd)
+            })
+            .collect()
+    }
+
+    pub(super) fn listener(fd: RawFd) -> io::Result<TcpListener> {
+        let mut ty: c_int = 0;
+        let mut len = mem::size_of::<c_int>() as socklen_t;
+
+        let ret = unsafe {
+            libc::getsockopt(
+                fd,
+                libc::SOL_SOCKET,
+                libc::SO_TYPE,
+                &mut ty as *mut c_int as *mut c_void,
+                &mut len,
+            )
+        };
+
+        if ret != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if ty != libc::SOCK_STREAM {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "not a stream socket"));
+        }
+
+        let listener = unsafe { TcpListener::from_raw_fd(fd) };
+        // Unix Domain Sockets are stream sockets too, but don't have an IP
+        // address.
+        listener.local_addr()?;
+        Ok(listener)
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::fs::{self, Permissions};
+    use std::io;
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+    use std::os::unix::net::UnixStream as StdUnixStream;
+    use std::path::Path;
+
+    use tokio::net::UnixListener;
+
+    pub(super) fn bind(path: &Path, mode: Option<u32>) -> UnixListener {
+        remove_stale(path);
+
+        let listener = UnixListener::bind(path)
+            .unwrap_or_else(|e| panic!("error binding to {}: {}", path.display(), e));
+
+        if let Some(mode) = mode {
+            fs::set_permissions(path, Permissions::from_mode(mode))
+                .unwrap_or_else(|e| panic!("error setting permissions of {}: {}", path.display(), e));
+        }
+
+        listener
+    }
+
+    // A socket file is left behind when a server exits without cleaning up.
+    // If nothing answers on it anymore, it's safe to remove.
+    fn remove_stale(path: &Path) {
+        let is_socket = fs::symlink_metadata(path)
+            .map(|meta| meta.file_type().is_socket())
+            .unwrap_or(false);
+
+        if !is_socket {
+            return;
+        }
+
+        match StdUnixStream::connect(path) {
+            Err(ref e) if e.kind() == io::ErrorKind::ConnectionRefused => {
+                debug!("removing stale unix socket: {}", path.display());
+                if let Err(e) = fs::remove_file(path) {
+                    debug!("error removing stale unix socket: {}", e);
+                }
+            },
+            _ => (),
+        }
+    }
+}
+
 pub trait IntoWarpService {
     type Service: WarpService + Send + Sync + 'static;
     fn into_warp_service(self) -> Self::Service;
@@ -184,7 +1189,7 @@ pub trait IntoWarpService {
 
 pub trait WarpService {
     type Reply: Future + Send;
-    fn call(&self, req: Request) -> Self::Reply;
+    fn call(&self, req: Request, conn_info: ConnInfo, timeout: Option<Duration>) -> Self::Reply;
 }
 
 
@@ -193,6 +1198,7 @@ pub trait WarpService {
 #[derive(Debug)]
 struct ReplyFuture<F> {
     inner: F,
+    _guard: RequestGuard,
 }
 
 impl<F> Future for ReplyFuture<F>
@@ -201,7 +1207,7 @@ where
     F::Item: Reply,
     F::Error: Reject,
 {
-    type Item = ::reply::Response;
+    type Item = Response;
     type Error = Never;
 
     #[inline]
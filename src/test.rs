@@ -4,6 +4,8 @@
 //! server, by making use of the [`RequestBuilder`](./struct.RequestBuilder.html) in this
 //! module.
 
+use std::net::SocketAddr;
+
 use bytes::Bytes;
 use futures::{future, Future, Stream};
 use http::{header::{HeaderName, HeaderValue}, HttpTryFrom, Response};
@@ -15,6 +17,7 @@ use ::filter::{Filter};
 use ::reject::Reject;
 use ::reply::{Reply, ReplySealed};
 use ::route::{self, Route};
+use ::transport::ConnInfo;
 use ::Request;
 
 use self::inner::OneOrTuple;
@@ -22,6 +25,7 @@ use self::inner::OneOrTuple;
 /// Starts a new test `RequestBuilder`.
 pub fn request() -> RequestBuilder {
     RequestBuilder {
+        conn_info: ConnInfo::default(),
         req: Request::default(),
     }
 }
@@ -32,6 +36,7 @@ pub fn request() -> RequestBuilder {
 #[must_use = "RequestBuilder does nothing on its own"]
 #[derive(Debug)]
 pub struct RequestBuilder {
+    conn_info: ConnInfo,
     req: Request,
 }
 
@@ -102,6 +107,25 @@ impl RequestBuilder {
         self
     }
 
+    /// Set the remote address of this request.
+    ///
+    /// Default is no remote address.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::net::SocketAddr;
+    ///
+    /// let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+    ///
+    /// let req = warp::test::request()
+    ///     .remote_addr(addr);
+    /// ```
+    pub fn remote_addr(mut self, addr: SocketAddr) -> Self {
+        self.conn_info.remote_addr = Some(addr);
+        self
+    }
+
     /// Set the bytes of this request body.
     ///
     /// Default is an empty body.
@@ -207,7 +231,7 @@ impl RequestBuilder {
         // TODO: de-duplicate this and apply_filter()
         assert!(!route::is_set(), "nested test filter calls");
 
-        let route = Route::new(self.req);
+        let route = Route::new(self.req, self.conn_info);
         let mut fut = route::set(&route, move || f.filter())
             .map(|rep| rep.into_response())
             .or_else(|rej| {
@@ -239,7 +263,7 @@ impl RequestBuilder {
     {
         assert!(!route::is_set(), "nested test filter calls");
 
-        let route = Route::new(self.req);
+        let route = Route::new(self.req, self.conn_info);
         let mut fut = route::set(&route, move || f.filter());
         let fut = future::poll_fn(move || {
             route::set(&route, || fut.poll())
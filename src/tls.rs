@@ -0,0 +1,169 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use futures::{Async, Future, Poll, Stream};
+use tokio_io::{AsyncRead, AsyncWrite};
+use tokio_rustls::{Accept, TlsAcceptor as Acceptor};
+use tokio_rustls::rustls::{NoClientAuth, ServerConfig, ServerSession};
+use tokio_rustls::rustls::internal::pemfile;
+
+use ::server::Protocol;
+use ::transport::{AddrIncoming, AddrStream, ConnInfo, Transport};
+
+pub(crate) fn configure(cert: &Path, key: &Path) -> ServerConfig {
+    let cert = {
+        let file = File::open(cert)
+            .unwrap_or_else(|e| panic!("tls cert file error: {}", e));
+        let mut rdr = BufReader::new(file);
+        pemfile::certs(&mut rdr)
+            .unwrap_or_else(|()| panic!("tls cert parse error"))
+    };
+
+    let key = {
+        let mut buf = Vec::new();
+        File::open(key)
+            .and_then(|mut file| file.read_to_end(&mut buf))
+            .unwrap_or_else(|e| panic!("tls key file error: {}", e));
+
+        // Try PKCS#8 first, falling back to a plain RSA key.
+        let mut keys = pemfile::pkcs8_private_keys(&mut buf.as_slice())
+            .unwrap_or_else(|()| panic!("tls key pkcs8 parse error"));
+        if keys.is_empty() {
+            keys = pemfile::rsa_private_keys(&mut buf.as_slice())
+                .unwrap_or_else(|()| panic!("tls key rsa parse error"));
+        }
+
+        keys
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| panic!("tls key file contains no private key"))
+    };
+
+    let mut tls = ServerConfig::new(NoClientAuth::new());
+    tls.set_single_cert(cert, key)
+        .unwrap_or_else(|e| panic!("tls failed: {}", e));
+    tls
+}
+
+// The protocols to advertise with ALPN, in order of preference.
+//
+// hyper detects the HTTP/2 preface on its own, so ALPN is only used to let
+// clients know HTTP/2 can be spoken at all.
+pub(crate) fn alpn_protocols(protocol: Protocol) -> Vec<String> {
+    match protocol {
+        Protocol::Auto => vec!["h2".into(), "http/1.1".into()],
+        Protocol::Http1 => vec!["http/1.1".into()],
+        Protocol::Http2 => vec!["h2".into()],
+    }
+}
+
+/// A stream of TLS connections, accepted from an `AddrIncoming`.
+///
+/// The handshake is not performed here, but lazily by the `TlsStream` once
+/// hyper starts reading from it. That way, a single bad handshake does not
+/// error the whole stream of incoming connections.
+pub(crate) struct TlsAcceptor {
+    acceptor: Acceptor,
+    incoming: AddrIncoming,
+}
+
+impl TlsAcceptor {
+    pub(crate) fn new(config: ServerConfig, incoming: AddrIncoming) -> TlsAcceptor {
+        TlsAcceptor {
+            acceptor: Acceptor::from(Arc::new(config)),
+            incoming,
+        }
+    }
+}
+
+impl Stream for TlsAcceptor {
+    type Item = TlsStream;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let sock = try_ready!(self.incoming.poll());
+        Ok(Async::Ready(sock.map(|sock| TlsStream {
+            conn_info: sock.conn_info(),
+            state: State::Handshaking(self.acceptor.accept(sock)),
+        })))
+    }
+}
+
+/// A TLS connection, which completes its handshake on first use.
+pub(crate) struct TlsStream {
+    conn_info: ConnInfo,
+    state: State,
+}
+
+enum State {
+    Handshaking(Accept<AddrStream>),
+    Streaming(::tokio_rustls::TlsStream<AddrStream, ServerSession>),
+}
+
+impl TlsStream {
+    fn poll_handshake(&mut self) -> Poll<(), io::Error> {
+        let stream = match self.state {
+            State::Handshaking(ref mut accept) => match accept.poll() {
+                Ok(Async::Ready(stream)) => stream,
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(err) => {
+                    debug!("tls handshake error: {}", err);
+                    return Err(err);
+                }
+            },
+            State::Streaming(_) => return Ok(Async::Ready(())),
+        };
+        trace!("tls handshake complete");
+        self.state = State::Streaming(stream);
+        Ok(Async::Ready(()))
+    }
+
+    fn stream(&mut self) -> io::Result<&mut ::tokio_rustls::TlsStream<AddrStream, ServerSession>> {
+        match self.poll_handshake()? {
+            Async::Ready(()) => match self.state {
+                State::Streaming(ref mut stream) => Ok(stream),
+                State::Handshaking(_) => unreachable!(),
+            },
+            Async::NotReady => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+}
+
+impl Transport for TlsStream {
+    fn conn_info(&self) -> ConnInfo {
+        self.conn_info
+    }
+}
+
+impl Read for TlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream()?.read(buf)
+    }
+}
+
+impl Write for TlsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream()?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream()?.flush()
+    }
+}
+
+impl AsyncRead for TlsStream {
+    unsafe fn prepare_uninitialized_buffer(&self, _: &mut [u8]) -> bool {
+        false
+    }
+}
+
+impl AsyncWrite for TlsStream {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        match self.state {
+            State::Handshaking(_) => Ok(Async::Ready(())),
+            State::Streaming(ref mut stream) => stream.shutdown(),
+        }
+    }
+}
@@ -0,0 +1,563 @@
+use std::cmp;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener as StdTcpListener};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use bytes::{Buf, BufMut, Bytes};
+use futures::{Async, Future, Poll, Stream};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::reactor::Handle;
+use tokio::timer::Delay;
+use tokio_io::{AsyncRead, AsyncWrite};
+
+use ::proxy::Handshakes;
+
+#[cfg(unix)]
+use ::filters::unix::PeerCred;
+
+/// Details about the connection a request was received on.
+///
+/// This is determined once per connection, when it's handed to hyper, and
+/// then copied into the `Route` of every request on that connection.
+///
+/// It's public only because `WarpService::call` is given it; its fields
+/// are read with filters such as `warp::addr::remote`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ConnInfo {
+    pub(crate) listener_addr: Option<SocketAddr>,
+    pub(crate) local_addr: Option<SocketAddr>,
+    pub(crate) remote_addr: Option<SocketAddr>,
+    #[cfg(unix)]
+    pub(crate) peer_cred: Option<PeerCred>,
+}
+
+/// An IO type that the server can serve, and that knows about its connection.
+pub(crate) trait Transport: AsyncRead + AsyncWrite {
+    fn conn_info(&self) -> ConnInfo;
+}
+
+impl Transport for AddrStream {
+    fn conn_info(&self) -> ConnInfo {
+        ConnInfo {
+            listener_addr: Some(self.listener_addr),
+            local_addr: Some(self.local_addr),
+            remote_addr: Some(self.remote_addr),
+            ..ConnInfo::default()
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Transport for ::tokio::net::UnixStream {
+    fn conn_info(&self) -> ConnInfo {
+        let peer_cred = ::filters::unix::peer_cred_of(self)
+            .map_err(|err| debug!("unix socket peer credentials error: {}", err))
+            .ok();
+
+        ConnInfo {
+            peer_cred,
+            ..ConnInfo::default()
+        }
+    }
+}
+
+/// Lifts any `AsyncRead + AsyncWrite` into a `Transport`, with no details
+/// known about its connection.
+///
+/// Used for the IO types of user supplied incoming streams.
+#[derive(Debug)]
+pub(crate) struct LiftIo<T>(pub(crate) T);
+
+impl<T: Read> Read for LiftIo<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl<T: Write> Write for LiftIo<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for LiftIo<T> {
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.0.prepare_uninitialized_buffer(buf)
+    }
+}
+
+impl<T: AsyncWrite> AsyncWrite for LiftIo<T> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.0.shutdown()
+    }
+}
+
+impl<T: AsyncRead + AsyncWrite> Transport for LiftIo<T> {
+    fn conn_info(&self) -> ConnInfo {
+        ConnInfo::default()
+    }
+}
+
+/// A connection being served, with the server's per-connection options
+/// applied.
+///
+/// The service of the connection is told when requests start and finish,
+/// with [`Conn::requests`](Conn::requests), so that reading the head of a
+/// request can be limited with a timeout.
+pub(crate) struct Conn<T> {
+    io: T,
+    head_timeout: Option<HeadTimeout>,
+    requests: Requests,
+}
+
+/// Counts the requests of a connection that have been started and finished
+/// by its service.
+#[derive(Clone, Debug)]
+pub(crate) struct Requests {
+    counts: Arc<Counts>,
+}
+
+#[derive(Debug)]
+struct Counts {
+    started: AtomicUsize,
+    finished: AtomicUsize,
+    stats: Arc<Stats>,
+}
+
+/// Live counters of all the connections of a server.
+#[derive(Debug, Default)]
+pub(crate) struct Stats {
+    connections: AtomicUsize,
+    in_flight: AtomicUsize,
+}
+
+/// Dropped when the service is done with a request.
+#[derive(Debug)]
+pub(crate) struct RequestGuard {
+    counts: Arc<Counts>,
+}
+
+struct HeadTimeout {
+    timeout: Duration,
+    delay: Option<Delay>,
+    // How many requests had been started when `delay` was armed. Once
+    // another request starts, its head has been read.
+    armed_at: usize,
+    sniffed: bool,
+}
+
+impl<T: Transport> Conn<T> {
+    pub(crate) fn new(io: T, head_timeout: Option<Duration>, stats: Arc<Stats>) -> Conn<T> {
+        stats.connections.fetch_add(1, Ordering::AcqRel);
+        Conn {
+            io,
+            // The timer starts right away, so that clients can't hold on
+            // to a connection without ever sending anything.
+            head_timeout: head_timeout.map(|timeout| HeadTimeout {
+                timeout,
+                delay: Some(Delay::new(Instant::now() + timeout)),
+                armed_at: 0,
+                sniffed: false,
+            }),
+            requests: Requests {
+                counts: Arc::new(Counts {
+                    started: AtomicUsize::new(0),
+                    finished: AtomicUsize::new(0),
+                    stats,
+                }),
+            },
+        }
+    }
+
+    pub(crate) fn requests(&self) -> &Requests {
+        &self.requests
+    }
+}
+
+impl Requests {
+    pub(crate) fn start(&self) -> RequestGuard {
+        self.counts.started.fetch_add(1, Ordering::AcqRel);
+        self.counts.stats.in_flight.fetch_add(1, Ordering::AcqRel);
+        RequestGuard {
+            counts: self.counts.clone(),
+        }
+    }
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        self.counts.finished.fetch_add(1, Ordering::AcqRel);
+        self.counts.stats.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl<T> Drop for Conn<T> {
+    fn drop(&mut self) {
+        self.requests.counts.stats.connections.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl Stats {
+    pub(crate) fn connections(&self) -> usize {
+        self.connections.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+}
+
+impl HeadTimeout {
+    fn poll_expired(&mut self, counts: &Counts) -> io::Result<()> {
+        if self.delay.is_some() && counts.started.load(Ordering::Acquire) != self.armed_at {
+            trace!("request head read, disarming head timeout");
+            self.delay = None;
+        }
+
+        if let Some(ref mut delay) = self.delay {
+            match delay.poll() {
+                Ok(Async::Ready(())) => {
+                    debug!("request head read timed out");
+                    return Err(io::Error::new(io::ErrorKind::TimedOut, "request head read timed out"));
+                },
+                Ok(Async::NotReady) => (),
+                Err(err) => {
+                    error!("head timeout timer error: {}", err);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Returns `false` if the timeout no longer applies to this connection.
+    fn on_read(&mut self, buf: &[u8], counts: &Counts) -> bool {
+        if buf.is_empty() {
+            return true;
+        }
+
+        if !self.sniffed {
+            self.sniffed = true;
+            // HTTP/2 connections stay open between requests, with frames
+            // going back and forth, so the timeout only applies to the
+            // connection preface.
+            if buf.starts_with(b"PRI ") {
+                trace!("http2 preface, disabling head timeout");
+                return false;
+            }
+        }
+
+        // Bytes read while no request is in progress are the start of the
+        // next request head.
+        let started = counts.started.load(Ordering::Acquire);
+        if self.delay.is_none() && started == counts.finished.load(Ordering::Acquire) {
+            self.delay = Some(Delay::new(Instant::now() + self.timeout));
+            self.armed_at = started;
+        }
+        true
+    }
+}
+
+impl<T: Read> Read for Conn<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(ref mut head) = self.head_timeout {
+            head.poll_expired(&self.requests.counts)?;
+        }
+        let n = self.io.read(buf)?;
+        let keep = match self.head_timeout {
+            Some(ref mut head) => head.on_read(&buf[..n], &self.requests.counts),
+            None => true,
+        };
+        if !keep {
+            self.head_timeout = None;
+        }
+        Ok(n)
+    }
+}
+
+impl<T: Write> Write for Conn<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.io.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.io.flush()
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for Conn<T> {
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.io.prepare_uninitialized_buffer(buf)
+    }
+}
+
+impl<T: AsyncWrite> AsyncWrite for Conn<T> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.io.shutdown()
+    }
+}
+
+impl<T: Transport> Transport for Conn<T> {
+    fn conn_info(&self) -> ConnInfo {
+        self.io.conn_info()
+    }
+}
+
+/// A stream of TCP connections, accepted from a bound listener.
+///
+/// This is much like hyper's `AddrIncoming`, but the accepted streams also
+/// know their local address, which can differ from the listener's address
+/// when bound to an unspecified address like `0.0.0.0`.
+#[must_use = "streams do nothing unless polled"]
+pub(crate) struct AddrIncoming {
+    addr: SocketAddr,
+    listener: TcpListener,
+    nodelay: bool,
+    proxy: Option<Handshakes>,
+    timeout: Option<Delay>,
+}
+
+impl AddrIncoming {
+    pub(crate) fn bind(addr: &SocketAddr) -> io::Result<AddrIncoming> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        Ok(AddrIncoming {
+            addr,
+            listener,
+            nodelay: false,
+            proxy: None,
+            timeout: None,
+        })
+    }
+
+    pub(crate) fn from_std(listener: StdTcpListener) -> io::Result<AddrIncoming> {
+        let listener = TcpListener::from_std(listener, &Handle::default())?;
+        let addr = listener.local_addr()?;
+        Ok(AddrIncoming {
+            addr,
+            listener,
+            nodelay: false,
+            proxy: None,
+            timeout: None,
+        })
+    }
+
+    pub(crate) fn set_nodelay(&mut self, enabled: bool) {
+        self.nodelay = enabled;
+    }
+
+    /// Expect a PROXY protocol header at the start of each connection,
+    /// which must be received within the `timeout`.
+    pub(crate) fn set_proxy_protocol(&mut self, timeout: Option<Duration>) {
+        self.proxy = Some(Handshakes::new(timeout));
+    }
+
+    pub(crate) fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    fn poll_accept(&mut self) -> Poll<Option<AddrStream>, io::Error> {
+        // Check if a previous timeout is active that was set by IO errors.
+        if let Some(ref mut to) = self.timeout {
+            match to.poll() {
+                Ok(Async::Ready(())) => {},
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(err) => {
+                    error!("sleep timer error: {}", err);
+                }
+            }
+        }
+        self.timeout = None;
+        loop {
+            match self.listener.poll_accept() {
+                Ok(Async::Ready((io, remote_addr))) => {
+                    if let Err(e) = io.set_nodelay(self.nodelay) {
+                        trace!("error trying to set TCP nodelay: {}", e);
+                    }
+                    let local_addr = io.local_addr().unwrap_or(self.addr);
+                    return Ok(Async::Ready(Some(AddrStream {
+                        io,
+                        listener_addr: self.addr,
+                        local_addr,
+                        read_buf: Bytes::new(),
+                        remote_addr,
+                    })));
+                },
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(e) => {
+                    // Connection errors can be ignored directly, continue by
+                    // accepting the next request.
+                    if is_connection_error(&e) {
+                        debug!("accepted connection already errored: {}", e);
+                        continue;
+                    }
+
+                    // Otherwise, this is likely running out of file
+                    // descriptors, so sleep a bit instead of spinning.
+                    error!("accept error: {}", e);
+                    let mut timeout = Delay::new(Instant::now() + Duration::from_secs(1));
+                    match timeout.poll() {
+                        Ok(Async::Ready(())) => continue,
+                        Ok(Async::NotReady) => {
+                            self.timeout = Some(timeout);
+                            return Ok(Async::NotReady);
+                        },
+                        Err(timer_err) => {
+                            error!("couldn't sleep on error, timer error: {}", timer_err);
+                            return Err(e);
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Accepts connections from several `AddrIncoming`s, as one stream.
+#[must_use = "streams do nothing unless polled"]
+pub(crate) struct ManyIncoming {
+    incomings: Vec<AddrIncoming>,
+    // Where to start polling next, so no listener starves the others.
+    next: usize,
+}
+
+impl ManyIncoming {
+    pub(crate) fn new(incomings: Vec<AddrIncoming>) -> ManyIncoming {
+        ManyIncoming {
+            incomings,
+            next: 0,
+        }
+    }
+}
+
+impl Stream for AddrIncoming {
+    type Item = AddrStream;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        if self.proxy.is_none() {
+            return self.poll_accept();
+        }
+
+        while let Async::Ready(Some(stream)) = self.poll_accept()? {
+            if let Some(ref mut proxy) = self.proxy {
+                proxy.push(stream);
+            }
+        }
+
+        match self.proxy {
+            Some(ref mut proxy) => Ok(proxy.poll().unwrap_or(Async::NotReady)),
+            None => unreachable!(),
+        }
+    }
+}
+
+impl Stream for ManyIncoming {
+    type Item = AddrStream;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let len = self.incomings.len();
+        for i in 0..len {
+            let idx = (self.next + i) % len;
+            // `AddrIncoming` never ends, so `None` isn't expected here.
+            if let Async::Ready(Some(stream)) = self.incomings[idx].poll()? {
+                self.next = (idx + 1) % len;
+                return Ok(Async::Ready(Some(stream)));
+            }
+        }
+        Ok(Async::NotReady)
+    }
+}
+
+fn is_connection_error(e: &io::Error) -> bool {
+    match e.kind() {
+        io::ErrorKind::ConnectionRefused |
+        io::ErrorKind::ConnectionAborted |
+        io::ErrorKind::ConnectionReset => true,
+        _ => false,
+    }
+}
+
+/// A TCP connection accepted by an `AddrIncoming`.
+#[derive(Debug)]
+pub(crate) struct AddrStream {
+    io: TcpStream,
+    listener_addr: SocketAddr,
+    local_addr: SocketAddr,
+    // Bytes already read from `io`, while looking for a PROXY protocol
+    // header, to be read again first.
+    read_buf: Bytes,
+    remote_addr: SocketAddr,
+}
+
+impl AddrStream {
+    /// Replaces the addresses of this connection with the ones received in
+    /// a PROXY protocol header.
+    pub(crate) fn set_proxied(&mut self, addrs: Option<(SocketAddr, SocketAddr)>, read_buf: Bytes) {
+        if let Some((src, dst)) = addrs {
+            self.remote_addr = src;
+            self.local_addr = dst;
+        }
+        self.read_buf = read_buf;
+    }
+}
+
+impl Read for AddrStream {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.read_buf.is_empty() {
+            let n = cmp::min(buf.len(), self.read_buf.len());
+            buf[..n].copy_from_slice(&self.read_buf.split_to(n));
+            return Ok(n);
+        }
+        self.io.read(buf)
+    }
+}
+
+impl Write for AddrStream {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.io.write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        // TcpStream::flush is a noop, so skip calling it...
+        Ok(())
+    }
+}
+
+impl AsyncRead for AddrStream {
+    #[inline]
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.io.prepare_uninitialized_buffer(buf)
+    }
+
+    #[inline]
+    fn read_buf<B: BufMut>(&mut self, buf: &mut B) -> Poll<usize, io::Error> {
+        if !self.read_buf.is_empty() {
+            let n = cmp::min(buf.remaining_mut(), self.read_buf.len());
+            buf.put_slice(&self.read_buf.split_to(n));
+            return Ok(Async::Ready(n));
+        }
+        self.io.read_buf(buf)
+    }
+}
+
+impl AsyncWrite for AddrStream {
+    #[inline]
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        AsyncWrite::shutdown(&mut self.io)
+    }
+
+    #[inline]
+    fn write_buf<B: Buf>(&mut self, buf: &mut B) -> Poll<usize, io::Error> {
+        self.io.write_buf(buf)
+    }
+}
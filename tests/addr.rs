@@ -0,0 +1,96 @@
+#![deny(warnings)]
+extern crate futures;
+extern crate hyper;
+extern crate pretty_env_logger;
+extern crate tokio;
+extern crate warp;
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+
+use warp::Filter;
+
+fn addrs() -> warp::filters::BoxedFilter<(String,)> {
+    warp::addr::remote()
+        .and(warp::addr::local())
+        .map(|remote: Option<SocketAddr>, local: Option<SocketAddr>| {
+            format!("{:?} {:?}", remote, local)
+        })
+        .boxed()
+}
+
+fn get(addr: SocketAddr) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n").unwrap();
+    let mut res = String::new();
+    stream.read_to_string(&mut res).unwrap();
+    let peer = stream.local_addr().unwrap();
+    let body = res.split("\r\n\r\n").nth(1).unwrap().to_string();
+    format!("{} {}", peer, body)
+}
+
+#[test]
+fn remote_addr_test_request() {
+    let _ = pretty_env_logger::try_init();
+
+    let addr: SocketAddr = "1.2.3.4:5678".parse().unwrap();
+    let req = warp::test::request()
+        .remote_addr(addr);
+
+    let extracted = req.filter(&warp::addr::remote()).unwrap();
+    assert_eq!(extracted, Some(addr));
+
+    let extracted = warp::test::request()
+        .filter(&warp::addr::remote())
+        .unwrap();
+    assert_eq!(extracted, None);
+}
+
+#[test]
+fn addrs_from_bind() {
+    let _ = pretty_env_logger::try_init();
+
+    let (addr, server) = warp::serve(addrs())
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+
+    let res = get(addr);
+    let mut parts = res.splitn(2, ' ');
+    let peer = parts.next().unwrap();
+    assert_eq!(parts.next().unwrap(), format!("Some({}) Some({})", peer, addr));
+}
+
+#[test]
+fn local_addr_of_unspecified_bind() {
+    let _ = pretty_env_logger::try_init();
+
+    let (addr, server) = warp::serve(warp::addr::local().map(|local: Option<SocketAddr>| {
+        local.unwrap().to_string()
+    }))
+        .bind_ephemeral(([0, 0, 0, 0], 0));
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+
+    // Bound to 0.0.0.0, but the connection itself arrives on loopback.
+    let res = get(([127, 0, 0, 1], addr.port()).into());
+    assert!(res.ends_with(&format!(" 127.0.0.1:{}", addr.port())), "{}", res);
+}
+
+#[test]
+fn addrs_none_with_serve_incoming() {
+    let _ = pretty_env_logger::try_init();
+
+    let listener = tokio::net::TcpListener::bind(&([127, 0, 0, 1], 0).into()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = warp::serve(addrs())
+        .serve_incoming(listener.incoming());
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+
+    let res = get(addr);
+    assert!(res.ends_with(" None None"), "{}", res);
+}
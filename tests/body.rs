@@ -1,9 +1,13 @@
 #![deny(warnings)]
+extern crate brotli;
 extern crate bytes;
+extern crate flate2;
 extern crate futures;
 extern crate pretty_env_logger;
 extern crate warp;
 
+use std::io::Write;
+
 use bytes::Buf;
 use futures::{Future, Stream};
 use warp::Filter;
@@ -64,6 +68,218 @@ fn content_length_limit() {
     assert_eq!(res.status(), 200, "under limit succeeds");
 }
 
+#[test]
+fn limit() {
+    let _ = pretty_env_logger::try_init();
+
+    let json = warp::body::limit(8)
+        .and(warp::body::json::<Vec<i32>>())
+        .map(|vec: Vec<i32>| format!("{:?}", vec));
+
+    let res = warp::test::request()
+        .body("[1, 2]")
+        .reply(&json);
+    assert_eq!(res.status(), 200, "under limit succeeds");
+    assert_eq!(res.body(), "[1, 2]");
+
+    let res = warp::test::request()
+        .body("[1, 2, 3, 4]")
+        .reply(&json);
+    assert_eq!(res.status(), 413, "over limit without content-length returns 413");
+
+    let res = warp::test::request()
+        .header("content-length", "999")
+        .body("[1]")
+        .reply(&json);
+    assert_eq!(res.status(), 413, "content-length over limit returns 413");
+
+    let tighter = warp::body::limit(8)
+        .and(warp::body::limit(4))
+        .and(warp::body::concat())
+        .map(|_| warp::reply());
+    let res = warp::test::request()
+        .body("[1, 2]")
+        .reply(&tighter);
+    assert_eq!(res.status(), 413, "smallest limit wins");
+
+    let stream = warp::body::limit(8)
+        .and(warp::body::stream())
+        .and_then(|body: warp::body::BodyStream| {
+            body.collect().then(|result| match result {
+                Ok(_) => Ok::<_, warp::Rejection>("read".to_owned()),
+                Err(err) => Ok(err.to_string()),
+            })
+        });
+    let res = warp::test::request()
+        .body("[1, 2, 3, 4]")
+        .reply(&stream);
+    assert_eq!(res.status(), 200);
+    assert!(
+        String::from_utf8_lossy(res.body()).contains("too large"),
+        "streams fail reading over limit: {:?}",
+        res.body(),
+    );
+}
+
+#[test]
+fn limit_or() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::body::limit(4)
+        .and(warp::path("small"))
+        .and(warp::body::concat())
+        .map(|_| "small")
+        .or(warp::path("big")
+            .and(warp::body::concat())
+            .map(|body: warp::body::FullBody| body.remaining().to_string()));
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/big")
+        .body("0123456789")
+        .reply(&route);
+    assert_eq!(res.status(), 200, "limit doesn't leak into the next branch");
+    assert_eq!(res.body(), "10");
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/small")
+        .body("0123456789")
+        .reply(&route);
+    assert_eq!(res.status(), 413);
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut enc = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    enc.write_all(data).unwrap();
+    enc.finish().unwrap()
+}
+
+#[test]
+fn decompress() {
+    let _ = pretty_env_logger::try_init();
+
+    let json = warp::body::decompress()
+        .and(warp::body::json::<Vec<i32>>())
+        .map(|vec: Vec<i32>| format!("{:?}", vec));
+
+    let res = warp::test::request()
+        .header("content-encoding", "gzip")
+        .body(gzip(b"[1, 2, 3]"))
+        .reply(&json);
+    assert_eq!(res.status(), 200, "gzip");
+    assert_eq!(res.body(), "[1, 2, 3]");
+
+    let mut enc = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    enc.write_all(b"[4, 5]").unwrap();
+    let res = warp::test::request()
+        .header("content-encoding", "deflate")
+        .body(enc.finish().unwrap())
+        .reply(&json);
+    assert_eq!(res.status(), 200, "deflate");
+    assert_eq!(res.body(), "[4, 5]");
+
+    let mut br = Vec::new();
+    {
+        let mut enc = brotli::CompressorWriter::new(&mut br, 4096, 5, 22);
+        enc.write_all(b"[6]").unwrap();
+    }
+    let res = warp::test::request()
+        .header("content-encoding", "br")
+        .body(br)
+        .reply(&json);
+    assert_eq!(res.status(), 200, "br");
+    assert_eq!(res.body(), "[6]");
+
+    let res = warp::test::request()
+        .body("[7]")
+        .reply(&json);
+    assert_eq!(res.status(), 200, "not encoded");
+    assert_eq!(res.body(), "[7]");
+
+    let res = warp::test::request()
+        .header("content-encoding", "compress")
+        .body("[8]")
+        .reply(&json);
+    assert_eq!(res.status(), 415, "unsupported encoding returns 415");
+
+    let res = warp::test::request()
+        .header("content-encoding", "gzip")
+        .body("[9]")
+        .reply(&json);
+    assert_eq!(res.status(), 400, "invalid gzip returns 400");
+
+    let truncated = gzip(b"[1, 2, 3]");
+    let res = warp::test::request()
+        .header("content-encoding", "gzip")
+        .body(&truncated[..truncated.len() - 4])
+        .reply(&json);
+    assert_eq!(res.status(), 400, "truncated gzip returns 400");
+}
+
+#[test]
+fn decompress_or() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::body::decompress()
+        .and(warp::path("a"))
+        .and(warp::body::concat())
+        .map(|_| "a")
+        .or(warp::path("raw")
+            .and(warp::body::concat())
+            .map(|body: warp::body::FullBody| body.remaining().to_string()));
+
+    let body = gzip(b"hello");
+    let len = body.len().to_string();
+    let res = warp::test::request()
+        .method("POST")
+        .path("/raw")
+        .header("content-encoding", "gzip")
+        .body(body)
+        .reply(&route);
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), &len, "decompress doesn't leak into the next branch");
+}
+
+#[test]
+fn decompress_limit() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::body::decompress()
+        .and(warp::body::limit(1024))
+        .and(warp::body::concat())
+        .map(|body: warp::body::FullBody| body.remaining().to_string());
+
+    let bomb = gzip(&vec![0; 10 * 1024 * 1024]);
+    assert!(bomb.len() < 1024 * 16);
+    let res = warp::test::request()
+        .header("content-encoding", "gzip")
+        .header("content-length", bomb.len().to_string())
+        .body(bomb)
+        .reply(&route);
+    assert_eq!(res.status(), 413, "decompressed size over limit returns 413");
+
+    let res = warp::test::request()
+        .header("content-encoding", "gzip")
+        .body(gzip(&[0; 1024]))
+        .reply(&route);
+    assert_eq!(res.status(), 200, "decompressed size under limit succeeds");
+    assert_eq!(res.body(), "1024");
+
+    let route = warp::body::content_length_limit(1024 * 16)
+        .and(warp::body::decompress())
+        .and(warp::body::concat())
+        .map(|body: warp::body::FullBody| body.remaining().to_string());
+
+    let bomb = gzip(&vec![0; 10 * 1024 * 1024]);
+    let res = warp::test::request()
+        .header("content-encoding", "gzip")
+        .header("content-length", bomb.len().to_string())
+        .body(bomb)
+        .reply(&route);
+    assert_eq!(res.status(), 413, "content_length_limit bounds the decompressed size");
+}
+
 #[test]
 fn json() {
     let _ = pretty_env_logger::try_init();
@@ -0,0 +1,171 @@
+#![deny(warnings)]
+extern crate brotli;
+extern crate flate2;
+extern crate pretty_env_logger;
+extern crate warp;
+
+use std::fs;
+use std::io::Read;
+
+use warp::Filter;
+
+fn text() -> String {
+    "Hello, compression! ".repeat(100)
+}
+
+fn gunzip(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    flate2::read::GzDecoder::new(data)
+        .read_to_end(&mut out)
+        .expect("gunzip");
+    out
+}
+
+#[test]
+fn negotiates() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any()
+        .map(text)
+        .with(warp::compression::auto());
+
+    let res = warp::test::request()
+        .header("accept-encoding", "gzip, deflate")
+        .reply(&route);
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-encoding"], "gzip");
+    assert_eq!(res.headers()["vary"], "accept-encoding");
+    assert!(!res.headers().contains_key("content-length"));
+    assert_eq!(gunzip(res.body()), text().as_bytes());
+
+    let res = warp::test::request()
+        .header("accept-encoding", "gzip;q=0.5, br;q=0.8")
+        .reply(&route);
+    assert_eq!(res.headers()["content-encoding"], "br");
+    let mut out = Vec::new();
+    brotli::Decompressor::new(&res.body()[..], 4096)
+        .read_to_end(&mut out)
+        .expect("brotli");
+    assert_eq!(out, text().as_bytes());
+
+    let res = warp::test::request()
+        .header("accept-encoding", "deflate")
+        .reply(&route);
+    assert_eq!(res.headers()["content-encoding"], "deflate");
+    let mut out = Vec::new();
+    flate2::read::ZlibDecoder::new(&res.body()[..])
+        .read_to_end(&mut out)
+        .expect("inflate");
+    assert_eq!(out, text().as_bytes());
+
+    let res = warp::test::request()
+        .header("accept-encoding", "br;q=0, *")
+        .reply(&route);
+    assert_eq!(res.headers()["content-encoding"], "gzip", "* allows others");
+
+    let res = warp::test::request()
+        .header("accept-encoding", "identity, *;q=0")
+        .reply(&route);
+    assert!(!res.headers().contains_key("content-encoding"), "identity only");
+    assert_eq!(res.headers()["vary"], "accept-encoding");
+    assert_eq!(res.body(), text().as_bytes());
+
+    let res = warp::test::request()
+        .reply(&route);
+    assert!(!res.headers().contains_key("content-encoding"), "no accept-encoding");
+    assert_eq!(res.body(), text().as_bytes());
+
+    let res = warp::test::request()
+        .method("HEAD")
+        .header("accept-encoding", "gzip")
+        .reply(&route);
+    assert!(!res.headers().contains_key("content-encoding"), "HEAD");
+}
+
+#[test]
+fn skips() {
+    let _ = pretty_env_logger::try_init();
+
+    let small = warp::any()
+        .map(|| "small")
+        .with(warp::compression::auto());
+    let res = warp::test::request()
+        .header("accept-encoding", "gzip")
+        .reply(&small);
+    assert!(!res.headers().contains_key("content-encoding"), "small bodies");
+    assert!(!res.headers().contains_key("vary"));
+    assert_eq!(res.body(), "small");
+
+    let min_size = warp::any()
+        .map(|| "small")
+        .with(warp::compression::auto().min_size(0));
+    let res = warp::test::request()
+        .header("accept-encoding", "gzip")
+        .reply(&min_size);
+    assert_eq!(res.headers()["content-encoding"], "gzip", "min_size");
+    assert_eq!(gunzip(res.body()), b"small");
+
+    let image = warp::any()
+        .map(|| warp::reply::with_header(text(), "content-type", "image/png"))
+        .with(warp::compression::auto());
+    let res = warp::test::request()
+        .header("accept-encoding", "gzip")
+        .reply(&image);
+    assert!(!res.headers().contains_key("content-encoding"), "compressed types");
+
+    let range = warp::any()
+        .map(|| warp::reply::with_header(text(), "content-range", "bytes 0-1999/5000"))
+        .with(warp::compression::auto());
+    let res = warp::test::request()
+        .header("accept-encoding", "gzip")
+        .reply(&range);
+    assert!(!res.headers().contains_key("content-encoding"), "content-range");
+
+    let encoded = warp::any()
+        .map(|| warp::reply::with_header(text(), "content-encoding", "identity"))
+        .with(warp::compression::auto());
+    let res = warp::test::request()
+        .header("accept-encoding", "gzip")
+        .reply(&encoded);
+    assert_eq!(res.headers()["content-encoding"], "identity", "content-encoding");
+}
+
+#[test]
+fn headers() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any()
+        .map(|| {
+            let reply = warp::reply::with_header(text(), "etag", "\"abc\"");
+            warp::reply::with_header(reply, "vary", "origin")
+        })
+        .with(warp::compression::auto());
+    let res = warp::test::request()
+        .header("accept-encoding", "gzip")
+        .reply(&route);
+    assert_eq!(res.headers()["etag"], "W/\"abc\"", "etag is weakened");
+    let vary = res.headers()
+        .get_all("vary")
+        .iter()
+        .map(|v| v.to_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(vary, ["origin", "accept-encoding"]);
+}
+
+#[test]
+fn file() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::fs::file("README.md")
+        .with(warp::compression::auto());
+    let res = warp::test::request()
+        .header("accept-encoding", "gzip")
+        .reply(&route);
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-encoding"], "gzip");
+    assert!(!res.headers().contains_key("content-length"));
+    assert!(!res.headers().contains_key("accept-ranges"));
+
+    let contents = fs::read("README.md").expect("fs::read README.md");
+    assert_eq!(gunzip(res.body()), contents);
+}
@@ -1,7 +1,11 @@
 #![deny(warnings)]
+extern crate futures;
 extern crate pretty_env_logger;
 extern crate warp;
 
+use std::time::Duration;
+
+use futures::future;
 use warp::Filter;
 
 #[test]
@@ -108,3 +112,31 @@ fn unify() {
         .unwrap();
     assert_eq!(ex, 1);
 }
+
+#[test]
+fn timeout() {
+    let _ = pretty_env_logger::try_init();
+
+    let fast = warp::any()
+        .map(|| "fast")
+        .timeout(Duration::from_secs(5));
+
+    let ex = warp::test::request()
+        .filter(&fast)
+        .unwrap();
+    assert_eq!(ex, "fast");
+
+    let slow = warp::any()
+        .and_then(|| future::empty::<&'static str, warp::Rejection>())
+        .timeout(Duration::from_millis(50));
+
+    let rej = warp::test::request()
+        .filter(&slow)
+        .unwrap_err();
+    let cause = rej.find_cause::<warp::reject::TimedOut>().unwrap();
+    assert!(!cause.is_reading_body());
+
+    let resp = warp::test::request()
+        .reply(&slow);
+    assert_eq!(resp.status(), 503);
+}
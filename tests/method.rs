@@ -64,3 +64,119 @@ fn bad_request_trumps_method_not_allowed() {
     assert_eq!(resp.status(), 400);
 }
 
+
+#[test]
+fn method_not_allowed_lists_allowed_methods() {
+    let _ = pretty_env_logger::try_init();
+    let hello = warp::path("hello");
+    let routes = hello.and(warp::get2()).map(warp::reply)
+        .or(hello.and(warp::post2()).map(warp::reply))
+        .or(hello.and(warp::get2()).and(warp::header::exact("foo", "bar")).map(warp::reply))
+        .or(warp::path("bye").and(warp::put2()).map(warp::reply));
+
+    let resp = warp::test::request()
+        .method("DELETE")
+        .path("/hello")
+        .reply(&routes);
+    assert_eq!(resp.status(), 405);
+    assert_eq!(resp.headers()["allow"], "GET, POST");
+
+    let resp = warp::test::request()
+        .method("DELETE")
+        .path("/bye")
+        .reply(&routes);
+    assert_eq!(resp.status(), 405);
+    assert_eq!(resp.headers()["allow"], "PUT");
+
+    let resp = warp::test::request()
+        .method("DELETE")
+        .path("/nope")
+        .reply(&routes);
+    assert_eq!(resp.status(), 404);
+    assert!(resp.headers().get("allow").is_none());
+}
+
+#[test]
+fn auto_head_options() {
+    let _ = pretty_env_logger::try_init();
+    let hello = warp::path("hello")
+        .and(warp::get2())
+        .and(warp::header::<u32>("x-num"))
+        .map(|num: u32| warp::reply::with_header(format!("hello {}", num), "x-num", num.to_string()));
+    let hello_post = warp::path("hello")
+        .and(warp::post2())
+        .map(warp::reply);
+    let bye = warp::path("bye")
+        .and(warp::head())
+        .map(|| warp::reply::with_header(warp::reply(), "x-explicit", "yes"));
+    let routes = hello
+        .or(hello_post)
+        .or(bye)
+        .with(warp::method::auto_head_options());
+
+    // HEAD runs the GET route, without its body.
+    let resp = warp::test::request()
+        .method("HEAD")
+        .path("/hello")
+        .header("x-num", "5")
+        .reply(&routes);
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["x-num"], "5");
+    assert_eq!(resp.headers()["content-length"], "7");
+    assert_eq!(resp.body(), "");
+
+    // ... including its rejections.
+    let resp = warp::test::request()
+        .method("HEAD")
+        .path("/hello")
+        .reply(&routes);
+    assert_eq!(resp.status(), 400);
+
+    // Explicit HEAD routes are used first.
+    let resp = warp::test::request()
+        .method("HEAD")
+        .path("/bye")
+        .reply(&routes);
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["x-explicit"], "yes");
+
+    let resp = warp::test::request()
+        .method("OPTIONS")
+        .path("/hello")
+        .reply(&routes);
+    assert_eq!(resp.status(), 204);
+    assert_eq!(resp.headers()["allow"], "GET, POST, HEAD, OPTIONS");
+
+    let resp = warp::test::request()
+        .method("OPTIONS")
+        .path("/bye")
+        .reply(&routes);
+    assert_eq!(resp.status(), 204);
+    assert_eq!(resp.headers()["allow"], "HEAD, OPTIONS");
+
+    // Unknown paths are still not found.
+    let resp = warp::test::request()
+        .method("OPTIONS")
+        .path("/nope")
+        .reply(&routes);
+    assert_eq!(resp.status(), 404);
+    let resp = warp::test::request()
+        .method("HEAD")
+        .path("/nope")
+        .reply(&routes);
+    assert_eq!(resp.status(), 404);
+
+    // Other methods are untouched.
+    let resp = warp::test::request()
+        .method("DELETE")
+        .path("/hello")
+        .reply(&routes);
+    assert_eq!(resp.status(), 405);
+    let resp = warp::test::request()
+        .method("GET")
+        .path("/hello")
+        .header("x-num", "5")
+        .reply(&routes);
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.body(), "hello 5");
+}
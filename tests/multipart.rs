@@ -0,0 +1,241 @@
+#![deny(warnings)]
+extern crate futures;
+extern crate pretty_env_logger;
+extern crate warp;
+
+use std::env;
+use std::fs;
+use std::process;
+
+use futures::{Future, Stream};
+use warp::Filter;
+use warp::multipart::{FormData, Part, PartData, SpooledPart};
+
+const BODY: &str = "\
+preamble\r\n\
+--X-BOUNDARY\r\n\
+content-disposition: form-data; name=\"title\"\r\n\
+\r\n\
+Hello, World!\r\n\
+--X-BOUNDARY\r\n\
+Content-Disposition: form-data; name=\"file\"; filename=\"a;b.txt\"\r\n\
+Content-Type: text/plain\r\n\
+\r\n\
+line 1\r\n--not the boundary\r\nline 2\r\n\
+--X-BOUNDARY\r\n\
+Content-Disposition: form-data; name=\"other\"; filename=\"fallback.txt\"; filename*=UTF-8''na%C3%AFve.txt\r\n\
+\r\n\
+\r\n\
+--X-BOUNDARY--\r\n\
+epilogue";
+
+fn form_request() -> warp::test::RequestBuilder {
+    warp::test::request()
+        .method("POST")
+        .header("content-type", "multipart/form-data; boundary=X-BOUNDARY")
+        .body(BODY)
+}
+
+fn describe(part: Part) -> impl Future<Item=String, Error=warp::Rejection> {
+    let head = format!(
+        "{} {:?} {:?}",
+        part.name(),
+        part.filename(),
+        part.content_type(),
+    );
+    part.concat2().map(move |data| {
+        format!("{} = {:?}\n", head, String::from_utf8_lossy(&data))
+    })
+}
+
+fn describe_all(form: FormData) -> impl Future<Item=String, Error=warp::Rejection> {
+    form.and_then(describe)
+        .collect()
+        .map(|parts| parts.concat())
+}
+
+#[test]
+fn parts() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::body::multipart()
+        .and_then(describe_all);
+
+    let res = form_request().reply(&route);
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        res.body(),
+        "title None None = \"Hello, World!\"\n\
+         file Some(\"a;b.txt\") Some(\"text/plain\") = \"line 1\\r\\n--not the boundary\\r\\nline 2\"\n\
+         other Some(\"naïve.txt\") None = \"\"\n"
+    );
+}
+
+#[test]
+fn skips_unread_parts() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::body::multipart()
+        .and_then(|form: FormData| {
+            form.map(|part| part.name().to_owned()).collect()
+        })
+        .map(|names: Vec<String>| names.join(","));
+
+    let res = form_request().reply(&route);
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "title,file,other");
+}
+
+#[test]
+fn rejects() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::body::multipart()
+        .and_then(describe_all);
+
+    let res = warp::test::request()
+        .header("content-type", "application/json")
+        .body("{}")
+        .reply(&route);
+    assert_eq!(res.status(), 415, "not multipart returns 415");
+
+    let res = warp::test::request()
+        .header("content-type", "multipart/form-data")
+        .body(BODY)
+        .reply(&route);
+    assert_eq!(res.status(), 400, "missing boundary returns 400");
+
+    let res = warp::test::request()
+        .header("content-type", "multipart/form-data; boundary=X-BOUNDARY")
+        .body(&BODY[..BODY.len() / 2])
+        .reply(&route);
+    assert_eq!(res.status(), 400, "truncated body returns 400");
+
+    let res = warp::test::request()
+        .header("content-type", "multipart/form-data; boundary=X-BOUNDARY")
+        .body("--X-BOUNDARY\r\nContent-Type: text/plain\r\n\r\nhi\r\n--X-BOUNDARY--")
+        .reply(&route);
+    assert_eq!(res.status(), 400, "part without a name returns 400");
+}
+
+#[test]
+fn limits() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::body::multipart()
+        .max_part_size(16)
+        .and_then(describe_all);
+    let res = form_request().reply(&route);
+    assert_eq!(res.status(), 413, "part over limit returns 413");
+
+    let route = warp::body::multipart()
+        .max_part_size(64)
+        .and_then(describe_all);
+    let res = form_request().reply(&route);
+    assert_eq!(res.status(), 200, "parts under limit are read");
+
+    let route = warp::body::multipart()
+        .max_length(64)
+        .and_then(describe_all);
+    let res = form_request().reply(&route);
+    assert_eq!(res.status(), 413, "body over limit returns 413");
+
+    let res = form_request()
+        .header("content-length", "999")
+        .reply(&warp::body::multipart().max_length(64).map(|_| warp::reply()));
+    assert_eq!(res.status(), 413, "content-length over limit returns 413");
+}
+
+#[test]
+fn spooled() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::body::multipart()
+        .and_then(|form: FormData| {
+            form.spooled().collect()
+        })
+        .map(|parts: Vec<SpooledPart>| {
+            let mut out = String::new();
+            for part in parts {
+                let data = match *part.data() {
+                    PartData::Bytes(ref bytes) => {
+                        String::from_utf8_lossy(bytes).into_owned()
+                    },
+                    PartData::File(ref file) => {
+                        let data = fs::read_to_string(file.path()).expect("read temp file");
+                        assert_eq!(file.len(), data.len() as u64);
+                        assert_owner_only(file.path());
+                        format!("file: {}", data)
+                    },
+                };
+                out.push_str(&format!("{} = {:?}\n", part.name(), data));
+
+                if let PartData::File(file) = part.into_data() {
+                    let path = file.path().to_owned();
+                    drop(file);
+                    assert!(!path.exists(), "temp file is removed on drop");
+                }
+            }
+            out
+        });
+
+    let res = form_request().reply(&route);
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        res.body(),
+        "title = \"Hello, World!\"\n\
+         file = \"file: line 1\\r\\n--not the boundary\\r\\nline 2\"\n\
+         other = \"file: \"\n"
+    );
+}
+
+#[test]
+fn persist() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::body::multipart()
+        .and_then(|form: FormData| {
+            form.spooled().collect()
+        })
+        .map(|parts: Vec<SpooledPart>| {
+            let file = parts
+                .into_iter()
+                .filter_map(|part| match part.into_data() {
+                    PartData::File(file) => Some(file),
+                    PartData::Bytes(_) => None,
+                })
+                .next()
+                .expect("a spooled file");
+
+            let dir = env::temp_dir().join(format!("warp-persist-{}", process::id()));
+            let target = dir.join("upload.txt");
+
+            // The directory doesn't exist yet, so the rename fails.
+            let err = file.persist(&target).unwrap_err();
+            let file = err.into_file();
+            assert!(file.path().exists(), "temp file is kept after a failed persist");
+
+            fs::create_dir_all(&dir).expect("create persist dir");
+            let temp = file.path().to_owned();
+            file.persist(&target).expect("persist");
+            assert!(!temp.exists());
+            let data = fs::read_to_string(&target).expect("read persisted file");
+            fs::remove_dir_all(&dir).expect("remove persist dir");
+            data
+        });
+
+    let res = form_request().reply(&route);
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "line 1\r\n--not the boundary\r\nline 2");
+}
+
+#[cfg(unix)]
+fn assert_owner_only(path: &::std::path::Path) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(path).expect("temp file metadata").permissions().mode();
+    assert_eq!(mode & 0o777, 0o600, "temp file is only readable by its owner");
+}
+
+#[cfg(not(unix))]
+fn assert_owner_only(_path: &::std::path::Path) {}
@@ -1,6 +1,8 @@
 #![deny(warnings)]
 extern crate pretty_env_logger;
 #[macro_use]
+extern crate serde_derive;
+#[macro_use]
 extern crate warp;
 
 use warp::Filter;
@@ -183,6 +185,91 @@ fn path_macro() {
         .path("/foo/bar");
     let p = path!("foo" / String);
     assert_eq!(req.filter(&p).unwrap(), "bar");
+
+    // only a prefix by default
+    let req = warp::test::request()
+        .path("/foo/bar/baz");
+    let p = path!("foo").and(path!("bar" / String));
+    assert_eq!(req.filter(&p).unwrap(), "baz");
+
+    let req = warp::test::request()
+        .path("/foo/bar/baz");
+    let p = path!("foo"; prefix).and(path!("bar" / String));
+    assert_eq!(req.filter(&p).unwrap(), "baz");
+
+    // unless it must end
+    let req = warp::test::request()
+        .path("/foo/bar");
+    let p = path!("foo"; end);
+    assert!(!req.matches(&p));
+
+    let req = warp::test::request()
+        .path("/foo/");
+    assert!(req.matches(&p));
+
+    // tail capture
+    let req = warp::test::request()
+        .path("/foo/bar/baz");
+    let p = path!("foo" / ..);
+    assert_eq!(req.filter(&p).unwrap().as_str(), "bar/baz");
+
+    let req = warp::test::request()
+        .path("/foo/bar/baz");
+    let p = path!(String / ..);
+    let (s, tail) = req.filter(&p).unwrap();
+    assert_eq!(s, "foo");
+    assert_eq!(tail.as_str(), "bar/baz");
+
+    let req = warp::test::request()
+        .path("/foo/bar");
+    let p = path!(..);
+    assert_eq!(req.filter(&p).unwrap().as_str(), "foo/bar");
+
+    // trailing slash policies
+    let strict = path!("foo" / u32; strict);
+    let lenient = path!("foo" / u32; lenient);
+    assert_eq!(warp::test::request().path("/foo/3").filter(&strict).unwrap(), 3);
+    assert!(!warp::test::request().path("/foo/3/").matches(&strict));
+    assert_eq!(warp::test::request().path("/foo/3/").filter(&lenient).unwrap(), 3);
+}
+
+#[test]
+fn end_with() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = path!("foo" / "bar"; redirect)
+        .map(warp::reply);
+
+    let res = warp::test::request()
+        .path("/foo/bar")
+        .reply(&route);
+    assert_eq!(res.status(), 200);
+
+    let res = warp::test::request()
+        .path("/foo/bar/?baz=quux")
+        .reply(&route);
+    assert_eq!(res.status(), 301);
+    assert_eq!(res.headers()["location"], "/foo/bar?baz=quux");
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/foo/bar/")
+        .reply(&route);
+    assert_eq!(res.status(), 308);
+    assert_eq!(res.headers()["location"], "/foo/bar");
+
+    // the index always matches
+    let index = warp::path::end_with(warp::path::TrailingSlash::Strict);
+    assert!(warp::test::request().path("/").matches(&index));
+
+    // a redirect is preferred to not found from other routes
+    let routes = warp::path("baz")
+        .map(warp::reply)
+        .or(route);
+    let res = warp::test::request()
+        .path("/foo/bar/")
+        .reply(&routes);
+    assert_eq!(res.status(), 301);
 }
 
 #[test]
@@ -318,3 +405,170 @@ fn peek_segments() {
     let segs = ex.segments().collect::<Vec<_>>();
     assert_eq!(segs, Vec::<&str>::new());
 }
+
+#[test]
+fn percent_decoded() {
+    let _ = pretty_env_logger::try_init();
+
+    let name = warp::path::param::<String>();
+    let ex = warp::test::request()
+        .path("/J%C3%BCrgen")
+        .filter(&name)
+        .unwrap();
+    assert_eq!(ex, "Jürgen");
+
+    let ex = warp::test::request()
+        .path("/a%2Fb")
+        .filter(&name)
+        .unwrap();
+    assert_eq!(ex, "a/b");
+
+    // invalid utf-8 is rejected
+    assert!(!warp::test::request()
+        .path("/%FF")
+        .matches(&name));
+
+    // path compares the decoded segment
+    assert!(warp::test::request()
+        .path("/caf%C3%A9")
+        .matches(&warp::path("café")));
+    assert!(warp::test::request()
+        .path("/%66oo")
+        .matches(&warp::path("foo")));
+
+    let raw = warp::path::param_raw::<String>();
+    let ex = warp::test::request()
+        .path("/a%2Fb")
+        .filter(&raw)
+        .unwrap();
+    assert_eq!(ex, "a%2Fb");
+}
+
+#[test]
+fn normalize() {
+    let _ = pretty_env_logger::try_init();
+
+    let normalize = warp::path::normalize();
+    let normalized = normalize.and(warp::path::full());
+    let norm = |path: &str| {
+        warp::test::request()
+            .path(path)
+            .filter(&normalized)
+            .unwrap()
+            .as_str()
+            .to_owned()
+    };
+
+    assert_eq!(norm("/foo/bar"), "/foo/bar");
+    assert_eq!(norm("//foo///bar/"), "/foo/bar/");
+    assert_eq!(norm("/foo/./bar"), "/foo/bar");
+    assert_eq!(norm("/foo/../bar"), "/bar");
+    assert_eq!(norm("/../../bar"), "/bar");
+    assert_eq!(norm("/foo/bar/.."), "/foo/");
+    assert_eq!(norm("/foo/.."), "/");
+    assert_eq!(norm("/%7Efoo/%c3%bc"), "/~foo/%C3%BC");
+    assert_eq!(norm("/caf%c3%a9/%e2%82%ac%F0%9f%a6%80"), "/caf%C3%A9/%E2%82%AC%F0%9F%A6%80");
+
+    // keeps the query
+    let ex = warp::test::request()
+        .path("/foo//bar?baz=quux")
+        .filter(&normalize.and(warp::query::raw()))
+        .unwrap();
+    assert_eq!(ex, "baz=quux");
+
+    // steps can be turned off
+    let ex = warp::test::request()
+        .path("//foo/./bar")
+        .filter(&normalize.merge_slashes(false).and(warp::path::full()))
+        .unwrap();
+    assert_eq!(ex.as_str(), "//foo/bar");
+    let ex = warp::test::request()
+        .path("//foo/./%7e")
+        .filter(&normalize.dot_segments(false).percent_encoding(false).and(warp::path::full()))
+        .unwrap();
+    assert_eq!(ex.as_str(), "/foo/./%7e");
+
+    // `..` never goes above what was already matched
+    let ex = warp::test::request()
+        .path("/foo/../../bar")
+        .filter(&warp::path("foo").and(normalize).and(warp::path::full()))
+        .unwrap();
+    assert_eq!(ex.as_str(), "/foo/bar");
+
+    let route = normalize
+        .and(warp::path("foo"))
+        .and(warp::path("bar"))
+        .and(warp::path::end());
+    assert!(warp::test::request()
+        .path("//foo/baz/../bar/")
+        .matches(&route));
+}
+
+#[test]
+fn template() {
+    let _ = pretty_env_logger::try_init();
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Repo {
+        org: String,
+        repo: String,
+        id: u32,
+    }
+
+    let repo = warp::path::template::<Repo>("/orgs/{org}/repos/{repo}/{id}");
+
+    let ex = warp::test::request()
+        .path("/orgs/rust-lang/repos/r%C3%BCst/7")
+        .filter(&repo)
+        .unwrap();
+    assert_eq!(ex, Repo {
+        org: "rust-lang".into(),
+        repo: "rüst".into(),
+        id: 7,
+    });
+
+    // trailing slash is fine, more segments aren't
+    assert!(warp::test::request()
+        .path("/orgs/a/repos/b/1/")
+        .matches(&repo));
+    assert!(!warp::test::request()
+        .path("/orgs/a/repos/b/1/c")
+        .matches(&repo));
+    assert!(!warp::test::request()
+        .path("/orgs/a/repos/b")
+        .matches(&repo));
+    let res = warp::test::request()
+        .path("/users/a/repos/b/1")
+        .reply(&repo.clone().map(|_| warp::reply()));
+    assert_eq!(res.status(), 404);
+
+    // invalid parameters are named
+    let rejection = warp::test::request()
+        .path("/orgs/a/repos/b/seven")
+        .filter(&repo)
+        .unwrap_err();
+    assert_eq!(rejection.status(), 400);
+    let cause = rejection.find_cause::<warp::path::InvalidParam>().unwrap();
+    assert_eq!(cause.name(), Some("id"));
+
+    // as are missing ones
+    let missing = warp::path::template::<Repo>("/orgs/{org}/repos/{repo}");
+    let rejection = warp::test::request()
+        .path("/orgs/a/repos/b")
+        .filter(&missing)
+        .unwrap_err();
+    let cause = rejection.find_cause::<warp::path::InvalidParam>().unwrap();
+    assert_eq!(cause.name(), Some("id"));
+    assert_eq!(cause.to_string(), "Invalid path parameter `id`: missing");
+
+    // tuples are in order, and `*` leaves the rest
+    let prefix = warp::path::template::<(String, u32)>("/{name}/{n}/*")
+        .and(warp::path::tail());
+    let ((name, n), tail) = warp::test::request()
+        .path("/foo/3/bar/baz")
+        .filter(&prefix)
+        .unwrap();
+    assert_eq!(name, "foo");
+    assert_eq!(n, 3);
+    assert_eq!(tail.as_str(), "bar/baz");
+}
@@ -0,0 +1,91 @@
+#![deny(warnings)]
+extern crate futures;
+extern crate hyper;
+extern crate pretty_env_logger;
+extern crate warp;
+
+use std::sync::{Arc, Mutex};
+
+use futures::{future, Async, Future, Stream};
+use futures::sync::oneshot;
+use warp::Filter;
+use hyper::{Body, Request};
+use hyper::service::Service;
+
+#[test]
+fn reload() {
+    let _ = pretty_env_logger::try_init();
+
+    let (handle, routes) = warp::reloadable(
+        warp::path("a")
+            .map(|| "a")
+            .boxed()
+    );
+
+    let req = warp::test::request().path("/a");
+    assert_eq!(req.reply(&routes).status(), 200);
+    let req = warp::test::request().path("/b");
+    assert_eq!(req.reply(&routes).status(), 404);
+
+    handle.reload(
+        warp::path("b")
+            .map(|| "b")
+            .boxed()
+    );
+
+    let req = warp::test::request().path("/a");
+    assert_eq!(req.reply(&routes).status(), 404);
+    let req = warp::test::request().path("/b");
+    assert_eq!(req.reply(&routes).status(), 200);
+
+    // Clones share the same handle.
+    handle.clone().reload(
+        warp::path("c")
+            .map(|| "c")
+            .boxed()
+    );
+    let req = warp::test::request().path("/c");
+    assert_eq!(req.reply(&routes.clone()).status(), 200);
+}
+
+#[test]
+fn in_flight_finishes_on_old_filter() {
+    let _ = pretty_env_logger::try_init();
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let rx = Arc::new(Mutex::new(Some(rx)));
+
+    let (handle, routes) = warp::reloadable(
+        warp::any()
+            .and_then(move || {
+                rx.lock()
+                    .unwrap()
+                    .take()
+                    .expect("only one request")
+                    .map(|()| "old")
+                    .map_err(|_| warp::reject::not_found())
+            })
+            .boxed()
+    );
+
+    let mut svc = warp::service(routes);
+    let mut old = svc.call(Request::new(Body::empty()));
+    let ready = future::poll_fn(|| Ok::<_, ()>(Async::Ready(old.poll().unwrap().is_ready())))
+        .wait()
+        .unwrap();
+    assert!(!ready);
+
+    handle.reload(
+        warp::any()
+            .map(|| "new")
+            .boxed()
+    );
+
+    let new = svc.call(Request::new(Body::empty()));
+    let body = new.wait().unwrap().into_body().concat2().wait().unwrap();
+    assert_eq!(&body[..], b"new");
+
+    tx.send(()).unwrap();
+    let body = old.wait().unwrap().into_body().concat2().wait().unwrap();
+    assert_eq!(&body[..], b"old");
+}
@@ -0,0 +1,268 @@
+#![deny(warnings)]
+extern crate pretty_env_logger;
+extern crate warp;
+
+use warp::Filter;
+use warp::http::Method;
+
+#[test]
+fn routes() {
+    let _ = pretty_env_logger::try_init();
+
+    let router = warp::router()
+        .route(Method::GET, "/", warp::any().map(|| "index"))
+        .route(Method::GET, "/users", warp::any().map(|| "users"))
+        .route(Method::GET, "/users/me", warp::any().map(|| "me"))
+        .route(Method::GET, "/users/{id}", warp::router::param("id").map(|id: u32| {
+            format!("user {}", id)
+        }))
+        .route(Method::DELETE, "/users/{id}", warp::router::param("id").map(|id: u32| {
+            format!("deleted {}", id)
+        }))
+        .route(Method::GET, "/users/{id}/posts/{post}", warp::router::param("id")
+            .and(warp::router::param("post"))
+            .map(|id: u32, post: String| format!("user {} post {}", id, post)))
+        .route(Method::GET, "/files/*", warp::path::tail().map(|tail: warp::path::Tail| {
+            format!("file {}", tail.as_str())
+        }));
+
+    let get = |path: &str| {
+        let res = warp::test::request()
+            .path(path)
+            .reply(&router);
+        (res.status().as_u16(), String::from_utf8(res.body().to_vec()).unwrap())
+    };
+
+    assert_eq!(get("/"), (200, "index".to_string()));
+    assert_eq!(get("/users"), (200, "users".to_string()));
+    assert_eq!(get("/users/"), (200, "users".to_string()));
+    assert_eq!(get("/users/me"), (200, "me".to_string()));
+    assert_eq!(get("/users/7"), (200, "user 7".to_string()));
+    assert_eq!(get("/users/7/posts/hello"), (200, "user 7 post hello".to_string()));
+    assert_eq!(get("/files/a/b.txt"), (200, "file a/b.txt".to_string()));
+    assert_eq!(get("/files"), (200, "file ".to_string()));
+
+    // Parameters match any segment, so other methods still apply.
+    assert_eq!(get("/users/seven").0, 405);
+    assert_eq!(get("/users/7/posts").0, 404);
+    assert_eq!(get("/nope").0, 404);
+    assert_eq!(get("/users//7").0, 404);
+
+    let res = warp::test::request()
+        .method("DELETE")
+        .path("/users/7")
+        .reply(&router);
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "deleted 7");
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/users/7")
+        .reply(&router);
+    assert_eq!(res.status(), 405);
+    assert_eq!(res.headers()["allow"], "GET, DELETE");
+}
+
+#[test]
+fn same_rejections_as_or() {
+    let _ = pretty_env_logger::try_init();
+
+    let router = warp::router()
+        .route(Method::GET, "/a/{id}", warp::router::param("id").map(|id: String| id))
+        .route(Method::POST, "/a/{id}", warp::router::param("id").map(|id: String| id))
+        .route(Method::GET, "/b", warp::header::<u32>("x-num").map(|n: u32| n.to_string()))
+        .route(Method::GET, "/b", warp::any().map(|| "fallback".to_string()))
+        .route(Method::PUT, "/c", warp::body::content_length_limit(4).map(|| "c".to_string()));
+
+    let chain = warp::path("a").and(warp::path::param::<String>()).and(warp::path::end()).and(warp::get2())
+        .or(warp::path("a").and(warp::path::param::<String>()).and(warp::path::end()).and(warp::post2()))
+        .unify()
+        .or(warp::path("b").and(warp::path::end()).and(warp::get2()).and(warp::header::<u32>("x-num")).map(|n: u32| n.to_string()))
+        .unify()
+        .or(warp::path("b").and(warp::path::end()).and(warp::get2()).map(|| "fallback".to_string()))
+        .unify()
+        .or(warp::path("c").and(warp::path::end()).and(warp::put2()).and(warp::body::content_length_limit(4)).map(|| "c".to_string()))
+        .unify();
+
+    let reqs = vec![
+        ("GET", "/a/1", None),
+        ("POST", "/a/2", None),
+        ("PUT", "/a/3", None),
+        ("GET", "/a/x", None),
+        ("GET", "/b", Some("5")),
+        ("GET", "/b", Some("x")),
+        ("GET", "/b", None),
+        ("DELETE", "/b", None),
+        ("PUT", "/c", None),
+        ("PUT", "/c", Some("5")),
+        ("GET", "/c", None),
+        ("GET", "/d", None),
+    ];
+
+    for (method, path, header) in reqs {
+        let req = || {
+            let req = warp::test::request()
+                .method(method)
+                .path(path);
+            match header {
+                Some(value) => req
+                    .header("x-num", value)
+                    .header("content-length", "100"),
+                None => req,
+            }
+        };
+        let expected = req().reply(&chain);
+        let res = req().reply(&router);
+        assert_eq!(res.status(), expected.status(), "{} {} {:?}", method, path, header);
+        assert_eq!(res.body(), expected.body(), "{} {} {:?}", method, path, header);
+    }
+}
+
+#[test]
+fn params_reset_by_or() {
+    let _ = pretty_env_logger::try_init();
+
+    let router = warp::router()
+        .route(Method::GET, "/{id}", warp::any().map(warp::reply));
+    let routes = router
+        .and_then(|_| Err::<String, _>(warp::reject::not_found()))
+        .or(warp::router::param("id").map(|id: String| id));
+
+    let res = warp::test::request()
+        .path("/5")
+        .reply(&routes);
+    assert_eq!(res.status(), 404, "a rejected branch doesn't leave its params behind");
+}
+
+#[test]
+fn mounted() {
+    let _ = pretty_env_logger::try_init();
+
+    let api = warp::router()
+        .route(Method::GET, "/users/{id}", warp::router::param("id").map(|id: u32| {
+            format!("user {}", id)
+        }));
+    let inner = warp::router()
+        .route(Method::GET, "/{id}", warp::router::param("id").map(|id: String| id));
+    let nested = warp::router()
+        .route(Method::GET, "/v/{id}/*", inner);
+
+    let routes = warp::path("api").and(api)
+        .or(nested);
+
+    let res = warp::test::request()
+        .path("/api/users/3")
+        .reply(&routes);
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "user 3");
+
+    let res = warp::test::request()
+        .path("/users/3")
+        .reply(&routes);
+    assert_eq!(res.status(), 404);
+
+    // The innermost router's parameter wins.
+    let res = warp::test::request()
+        .path("/v/outer/inner")
+        .reply(&routes);
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "inner");
+}
+
+#[test]
+#[should_panic(expected = "should start with a slash")]
+fn template_without_slash() {
+    let _ = warp::router()
+        .route(Method::GET, "users", warp::any().map(warp::reply));
+}
+
+#[test]
+#[should_panic(expected = "should be the last segment")]
+fn template_tail_not_last() {
+    let _ = warp::router()
+        .route(Method::GET, "/*/users", warp::any().map(warp::reply));
+}
+
+#[test]
+fn urls_for_router() {
+    let _ = pretty_env_logger::try_init();
+
+    let router = warp::router()
+        .route(Method::GET, "/", warp::any().map(|| "index"))
+        .name("index")
+        .route(Method::GET, "/users/{id}", warp::router::param("id").map(|id: u32| {
+            format!("user {}", id)
+        }))
+        .name("user_detail")
+        .route(Method::DELETE, "/users/{id}", warp::any().map(|| "deleted"))
+        .name("user_detail")
+        .route(Method::GET, "/orgs/{org}/repos/{repo}", warp::any().map(|| "repo"))
+        .name("repo")
+        .route(Method::GET, "/files/*", warp::any().map(|| "file"))
+        .name("files");
+
+    let urls = warp::routes::urls(&router);
+    assert_eq!(urls.get::<()>("index").unwrap().url(()).unwrap(), "/");
+    let user_detail = urls.get::<(u32,)>("user_detail").unwrap();
+    assert_eq!(user_detail.url((5,)).unwrap(), "/users/5");
+    let repo = urls.get::<(&str, &str)>("repo").unwrap();
+    assert_eq!(repo.url(("rüst", "a b/c")).unwrap(), "/orgs/r%C3%BCst/repos/a%20b%2Fc");
+    assert_eq!(repo.url(("acme", "..")).unwrap(), "/orgs/acme/repos/%2E%2E");
+    let files = urls.get::<(&str,)>("files").unwrap();
+    assert_eq!(files.url(("a b/c.txt",)).unwrap(), "/files/a%20b/c.txt");
+    let repo = urls.get::<(&str, u32)>("repo").unwrap();
+    assert_eq!(repo.url(("acme", 5)).unwrap(), "/orgs/acme/repos/5", "values of different types");
+
+    // built URLs route back to their parameters
+    let url = user_detail.url((42,)).unwrap();
+    let res = warp::test::request()
+        .path(&url)
+        .reply(&router);
+    assert_eq!(res.body(), "user 42");
+
+    let err = repo.url(("", 5)).unwrap_err();
+    assert_eq!(err.to_string(), "empty parameter `org` for route \"repo\"");
+    let err = urls.get::<(u32,)>("nope").unwrap_err();
+    assert_eq!(err.to_string(), "no route named \"nope\"");
+}
+
+#[test]
+#[should_panic(expected = "is already used")]
+fn name_conflict() {
+    let _ = warp::router()
+        .route(Method::GET, "/a", warp::any().map(warp::reply))
+        .name("a")
+        .route(Method::GET, "/b", warp::any().map(warp::reply))
+        .name("a");
+}
+
+#[test]
+#[should_panic(expected = "is repeated")]
+fn name_repeated_param() {
+    let _ = warp::router()
+        .route(Method::GET, "/{a}/{a}", warp::any().map(warp::reply))
+        .name("a");
+}
+
+#[test]
+fn urls() {
+    let _ = pretty_env_logger::try_init();
+
+    let router = warp::router()
+        .route(Method::GET, "/users/{id}", warp::any().map(warp::reply))
+        .name("user_detail")
+        .route(Method::GET, "/files/*", warp::any().map(warp::reply))
+        .name("files");
+    let routes = warp::path("api").and(router);
+
+    let urls = warp::routes::urls(&routes);
+    let user_detail = urls.get::<(u32,)>("user_detail").unwrap();
+    assert_eq!(user_detail.url((5,)).unwrap(), "/api/users/5", "includes the prefix");
+    let files = urls.get::<(&str,)>("files").unwrap();
+    assert_eq!(files.url(("a b/c.txt",)).unwrap(), "/api/files/a%20b/c.txt");
+
+    let err = urls.get::<()>("user_detail").unwrap_err();
+    assert_eq!(err.to_string(), "missing parameters `id` for route \"user_detail\"");
+    let err = urls.get::<(u32, u32)>("user_detail").unwrap_err();
+    assert_eq!(err.to_string(), "1 unexpected parameters for route \"user_detail\"");
+}
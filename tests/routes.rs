@@ -0,0 +1,192 @@
+#![deny(warnings)]
+extern crate pretty_env_logger;
+#[macro_use]
+extern crate warp;
+
+use std::any::TypeId;
+
+use warp::Filter;
+use warp::http::Method;
+use warp::routes::Description;
+
+#[test]
+fn describe() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::get2()
+        .and(warp::path("users"))
+        .and(warp::path::param::<u32>())
+        .and(warp::path::end())
+        .and(warp::header::exact("accept", "text/html"))
+        .map(|_id| warp::reply());
+
+    assert_eq!(route.describe(), Description::And(vec![
+        Description::Method(Method::GET),
+        Description::Path("users".into()),
+        Description::Param(TypeId::of::<u32>()),
+        Description::End,
+        Description::Header {
+            name: "accept".into(),
+            value: Some("text/html".into()),
+        },
+    ]));
+
+    // boxed filters still describe themselves
+    let boxed = warp::path("users").boxed();
+    assert_eq!(boxed.describe(), Description::Path("users".into()));
+    assert_eq!(format!("{:?}", boxed), "BoxedFilter(Path(\"users\"))");
+
+    // unknown checks are opaque
+    let json = warp::body::json::<u32>();
+    assert_eq!(json.describe(), Description::Opaque);
+    let checked = warp::any().and_then(|| Ok::<_, warp::Rejection>("ok"));
+    assert_eq!(checked.describe(), Description::Opaque);
+}
+
+#[test]
+fn list() {
+    let _ = pretty_env_logger::try_init();
+
+    let users = warp::path("users");
+    let routes = warp::get2()
+        .and(users)
+        .and(warp::path::end())
+        .map(warp::reply)
+        .or(warp::post2()
+            .and(users)
+            .and(warp::header::<String>("content-type"))
+            .and(warp::body::json())
+            .map(|_: String, _: u32| warp::reply()))
+        .or(warp::path("static").and(warp::path::tail()).map(|_| warp::reply()))
+        .or(warp::router()
+            .route(Method::DELETE, "/users/{id}", warp::any().map(warp::reply)));
+
+    let listing = warp::routes::list(&routes)
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(listing, vec![
+        "GET /users",
+        "POST /users (prefix) [content-type] (custom)",
+        "/static/*",
+        "DELETE /users/{id}",
+    ]);
+}
+
+#[test]
+fn shadowed() {
+    let _ = pretty_env_logger::try_init();
+
+    let reply = || warp::any().map(warp::reply);
+
+    let routes = warp::path("users")
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(reply())
+        .or(warp::path("users").and(warp::path("me")).and(warp::path::end()).and(reply()));
+    let shadowed = warp::routes::shadowed(&routes);
+    assert_eq!(shadowed.len(), 1);
+    assert_eq!(shadowed[0].later().to_string(), "/users/me");
+    assert_eq!(shadowed[0].earlier().to_string(), "/users/{param}");
+    assert_eq!(
+        shadowed[0].to_string(),
+        "route `/users/me` is shadowed by earlier route `/users/{param}`"
+    );
+
+    // the other way around is fine
+    let routes = warp::path("users").and(warp::path("me")).and(warp::path::end()).and(reply())
+        .or(warp::path("users").and(warp::path::param::<String>()).and(warp::path::end()).and(reply()));
+    assert!(warp::routes::shadowed(&routes).is_empty());
+
+    // a typed param doesn't shadow a literal segment
+    let routes = warp::path::param::<u32>().and(reply())
+        .or(warp::path("me").and(reply()));
+    assert!(warp::routes::shadowed(&routes).is_empty());
+
+    // prefixes shadow longer paths, but only with the same method and headers
+    let routes = warp::get2().and(warp::path("api")).and(reply())
+        .or(warp::get2().and(warp::path("api")).and(warp::path("v1")).and(warp::path::end()).and(reply()))
+        .or(warp::post2().and(warp::path("api")).and(reply()));
+    assert_eq!(warp::routes::shadowed(&routes).len(), 1);
+
+    let routes = warp::header::<String>("x-a").and(reply())
+        .or(reply());
+    assert!(warp::routes::shadowed(&routes).is_empty());
+
+    // parsing a header may reject, even as a String if it isn't UTF-8
+    let routes = warp::header::<u32>("x").and(reply())
+        .or(warp::header::<String>("x").and(reply()));
+    assert!(warp::routes::shadowed(&routes).is_empty());
+
+    let routes = warp::header::<String>("x").and(reply())
+        .or(warp::header::<u32>("x").and(reply()));
+    assert!(warp::routes::shadowed(&routes).is_empty());
+
+    // an exact value only matches itself, but ignoring case matches others
+    let routes = warp::header::exact("x", "a").and(reply())
+        .or(warp::header::exact("x", "a").and(reply()));
+    assert_eq!(warp::routes::shadowed(&routes).len(), 1);
+
+    let routes = warp::header::exact("x", "a").and(reply())
+        .or(warp::header::exact_ignore_case("x", "a").and(reply()));
+    assert!(warp::routes::shadowed(&routes).is_empty());
+
+    // opaque routes may reject, so never shadow
+    let routes = warp::path("a").and_then(|| Ok::<_, warp::Rejection>("ok"))
+        .or(warp::path("a").map(|| "ok"));
+    assert!(warp::routes::shadowed(&routes).is_empty());
+}
+
+#[test]
+fn named() {
+    let _ = pretty_env_logger::try_init();
+
+    let user = warp::routes::named("user_detail", path!("users" / u32))
+        .map(|id: u32| id.to_string());
+    let repo = warp::path("api")
+        .and(warp::routes::named("repo", path!("orgs" / String / "repos" / u32)))
+        .map(|_: String, _: u32| "repo");
+    let file = warp::routes::named("file", warp::path("files").and(warp::path::tail()))
+        .map(|_| "file");
+    let index = warp::routes::named("index", warp::path::end())
+        .map(|| "index");
+    let routes = user.or(repo).or(file).or(index);
+
+    let urls = warp::routes::urls(&routes);
+    let user_detail = urls.get::<(u32,)>("user_detail").unwrap();
+    assert_eq!(user_detail.url((5,)).unwrap(), "/users/5");
+    assert_eq!(
+        urls.get::<(&str, u32)>("repo").unwrap().url(("a b/c", 7)).unwrap(),
+        "/api/orgs/a%20b%2Fc/repos/7"
+    );
+    assert_eq!(
+        urls.get::<(String,)>("file").unwrap().url(("a/b.txt".to_owned(),)).unwrap(),
+        "/files/a/b.txt"
+    );
+    assert_eq!(urls.get::<()>("index").unwrap().url(()).unwrap(), "/");
+
+    // built URLs route back to their parameters
+    let res = warp::test::request()
+        .path(&user_detail.url((42,)).unwrap())
+        .reply(&routes);
+    assert_eq!(res.body(), "42");
+
+    // parameter counts are checked when getting a route, at startup
+    let err = urls.get::<()>("repo").unwrap_err();
+    assert_eq!(err.to_string(), "missing parameters `param`, `param` for route \"repo\"");
+    let err = urls.get::<(u32, u32)>("user_detail").unwrap_err();
+    assert_eq!(err.to_string(), "1 unexpected parameters for route \"user_detail\"");
+    let err = urls.get::<()>("nope").unwrap_err();
+    assert_eq!(err.to_string(), "no route named \"nope\"");
+
+    let err = urls.get::<(&str,)>("user_detail").unwrap().url(("",)).unwrap_err();
+    assert_eq!(err.to_string(), "empty parameter `param` for route \"user_detail\"");
+}
+
+#[test]
+#[should_panic(expected = "is used for different paths")]
+fn named_conflict() {
+    let routes = warp::routes::named("a", warp::path("a"))
+        .or(warp::routes::named("a", warp::path("b")));
+    let _ = warp::routes::urls(&routes);
+}
@@ -0,0 +1,457 @@
+#![deny(warnings)]
+extern crate futures;
+extern crate hyper;
+extern crate pretty_env_logger;
+extern crate tokio;
+extern crate warp;
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener as StdTcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use futures::{Future, Stream};
+use futures::sync::oneshot;
+use hyper::{Body, Client, Request, Version};
+use tokio::timer::Timeout;
+use warp::Filter;
+
+#[test]
+fn http2_prior_knowledge() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::post2()
+        .and(warp::path("echo"))
+        .and(warp::header::<String>("x-foo"))
+        .and(warp::body::concat())
+        .map(|foo: String, body: warp::body::FullBody| {
+            use warp::Buf;
+            format!("{} {}", foo, String::from_utf8_lossy(body.bytes()))
+        });
+
+    let (addr, server) = warp::serve(routes)
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+
+    let client = Client::builder()
+        .http2_only(true)
+        .build_http::<Body>();
+
+    let req = Request::post(format!("http://{}/echo", addr))
+        .header("x-foo", "bar")
+        .body(Body::from("baz"))
+        .unwrap();
+
+    let (version, body) = rt.block_on(client.request(req).and_then(|res| {
+        let version = res.version();
+        res.into_body().concat2().map(move |body| (version, body))
+    })).unwrap();
+
+    assert_eq!(version, Version::HTTP_2);
+    assert_eq!(&body[..], b"bar baz");
+}
+
+#[test]
+fn http2_only_refuses_http1() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::any().map(warp::reply);
+
+    let (addr, server) = warp::serve(routes)
+        .http2_only(true)
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+
+    let h1 = Client::new();
+    let uri = format!("http://{}/", addr).parse::<hyper::Uri>().unwrap();
+    assert!(rt.block_on(h1.get(uri.clone())).is_err());
+
+    let h2 = Client::builder()
+        .http2_only(true)
+        .build_http::<Body>();
+    let res = rt.block_on(h2.get(uri)).unwrap();
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.version(), Version::HTTP_2);
+}
+
+fn raw_request(addr: SocketAddr, req: &[u8]) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(req).unwrap();
+    let mut res = String::new();
+    stream.read_to_string(&mut res).unwrap();
+    res
+}
+
+#[test]
+fn http1_only_refuses_http2() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::any().map(warp::reply);
+
+    let (addr, server) = warp::serve(routes)
+        .http1_only(true)
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+
+    let uri = format!("http://{}/", addr).parse::<hyper::Uri>().unwrap();
+    let h2 = Client::builder()
+        .http2_only(true)
+        .build_http::<Body>();
+    assert!(rt.block_on(h2.get(uri.clone())).is_err());
+
+    let res = rt.block_on(Client::new().get(uri)).unwrap();
+    assert_eq!(res.status(), 200);
+}
+
+#[test]
+fn keep_alive_disabled() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::any().map(warp::reply);
+
+    let (addr, server) = warp::serve(routes)
+        .keep_alive(false)
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+
+    // With keep-alive, this would hang waiting for the next request.
+    let res = raw_request(addr, b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n");
+    assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{}", res);
+}
+
+#[test]
+fn max_headers() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::any().map(warp::reply);
+
+    let (addr, server) = warp::serve(routes)
+        .max_headers(3)
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+
+    let res = raw_request(addr, b"GET / HTTP/1.1\r\nhost: localhost\r\na: 1\r\nconnection: close\r\n\r\n");
+    assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{}", res);
+
+    let res = raw_request(addr, b"GET / HTTP/1.1\r\nhost: localhost\r\na: 1\r\nb: 2\r\nconnection: close\r\n\r\n");
+    assert!(res.starts_with("HTTP/1.1 431 Request Header Fields Too Large\r\n"), "{}", res);
+}
+
+#[test]
+fn max_header_size() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::any().map(warp::reply);
+
+    let (addr, server) = warp::serve(routes)
+        .max_header_size(64)
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+
+    let res = raw_request(addr, b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n");
+    assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{}", res);
+
+    let big = format!("GET / HTTP/1.1\r\nx-big: {}\r\nconnection: close\r\n\r\n", "a".repeat(64));
+    let res = raw_request(addr, big.as_bytes());
+    assert!(res.starts_with("HTTP/1.1 431 Request Header Fields Too Large\r\n"), "{}", res);
+}
+
+#[test]
+fn header_read_timeout() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::body::concat()
+        .map(|body: warp::body::FullBody| {
+            use warp::Buf;
+            format!("{}", body.remaining())
+        });
+
+    let (addr, server) = warp::serve(routes)
+        .header_read_timeout(Duration::from_millis(200))
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+
+    // A client that never finishes its headers is disconnected.
+    let start = Instant::now();
+    let res = {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\nhost: loc").unwrap();
+        let mut res = Vec::new();
+        stream.read_to_end(&mut res).unwrap();
+        res
+    };
+    assert!(res.is_empty());
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // A slow body, once the head has been read, is fine.
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(b"POST / HTTP/1.1\r\nhost: localhost\r\ncontent-length: 6\r\n\r\nabc").unwrap();
+    thread::sleep(Duration::from_millis(400));
+    stream.write_all(b"def").unwrap();
+
+    let mut res = String::new();
+    while !res.ends_with("\r\n\r\n6") {
+        let mut buf = [0; 1024];
+        let n = stream.read(&mut buf).unwrap();
+        assert_ne!(n, 0, "unexpected eof: {}", res);
+        res.push_str(&String::from_utf8_lossy(&buf[..n]));
+    }
+    assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{}", res);
+    assert!(res.ends_with("\r\n\r\n6"), "{}", res);
+
+    // And so is an idle keep-alive connection, until the next request starts.
+    thread::sleep(Duration::from_millis(400));
+    stream.write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n").unwrap();
+    let mut res = String::new();
+    stream.read_to_string(&mut res).unwrap();
+    assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{}", res);
+}
+
+#[test]
+fn request_timeout() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::path("hang")
+        .and_then(|| futures::future::empty::<&'static str, warp::Rejection>())
+        .or(warp::path("body").and(warp::body::concat()).map(|_| "body"))
+        .or(warp::path("drop").and(warp::body::stream()).and_then(|body| {
+            drop(body);
+            futures::future::empty::<&'static str, warp::Rejection>()
+        }));
+
+    let (addr, server) = warp::serve(routes)
+        .request_timeout(Duration::from_millis(100))
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+
+    let res = raw_request(addr, b"GET /hang HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n");
+    assert!(res.starts_with("HTTP/1.1 503 Service Unavailable\r\n"), "{}", res);
+
+    // The body never finishes arriving.
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(b"POST /body HTTP/1.1\r\nhost: localhost\r\ncontent-length: 10\r\n\r\nabc").unwrap();
+    let mut buf = [0; 1024];
+    let n = stream.read(&mut buf).unwrap();
+    let res = String::from_utf8_lossy(&buf[..n]);
+    assert!(res.starts_with("HTTP/1.1 408 Request Timeout\r\n"), "{}", res);
+
+    // The handler stopped reading the body, so it's the handler that hangs.
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(b"POST /drop HTTP/1.1\r\nhost: localhost\r\ncontent-length: 10\r\n\r\nabc").unwrap();
+    let n = stream.read(&mut buf).unwrap();
+    let res = String::from_utf8_lossy(&buf[..n]);
+    assert!(res.starts_with("HTTP/1.1 503 Service Unavailable\r\n"), "{}", res);
+
+    let res = raw_request(addr, b"POST /body HTTP/1.1\r\nhost: localhost\r\ncontent-length: 3\r\nconnection: close\r\n\r\nabc");
+    assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{}", res);
+}
+
+#[test]
+fn handle_graceful_shutdown() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::any().map(|| "hello");
+
+    let (handle, server) = warp::serve(routes)
+        .bind_with_handle(([127, 0, 0, 1], 0));
+    let addr = handle.local_addr();
+
+    let (tx, rx) = oneshot::channel();
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server.then(move |_| tx.send(())));
+
+    // Leave a keep-alive connection open.
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n").unwrap();
+    let mut res = Vec::new();
+    let mut buf = [0; 1024];
+    while !res.ends_with(b"hello") {
+        let n = stream.read(&mut buf).unwrap();
+        assert_ne!(n, 0, "unexpected eof");
+        res.extend_from_slice(&buf[..n]);
+    }
+    assert!(res.starts_with(b"HTTP/1.1 200 OK\r\n"));
+    assert_eq!(handle.open_connections(), 1);
+    assert_eq!(handle.in_flight_requests(), 0);
+
+    handle.shutdown();
+
+    rt.block_on(Timeout::new(rx, Duration::from_secs(5))).unwrap();
+    assert_eq!(stream.read(&mut buf).unwrap(), 0);
+    assert_eq!(handle.open_connections(), 0);
+    assert!(TcpStream::connect(addr).is_err());
+}
+
+#[test]
+fn handle_shutdown_timeout() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::any()
+        .and_then(|| futures::future::empty::<&'static str, warp::Rejection>());
+
+    let (handle, server) = warp::serve(routes)
+        .bind_with_handle(([127, 0, 0, 1], 0));
+
+    let (tx, rx) = oneshot::channel();
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server.then(move |_| tx.send(())));
+
+    let mut stream = TcpStream::connect(handle.local_addr()).unwrap();
+    stream.write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n").unwrap();
+
+    let start = Instant::now();
+    while handle.in_flight_requests() == 0 {
+        assert!(start.elapsed() < Duration::from_secs(5), "request never started");
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(handle.open_connections(), 1);
+
+    handle.shutdown_timeout(Duration::from_millis(100));
+
+    rt.block_on(Timeout::new(rx, Duration::from_secs(5))).unwrap();
+    let mut buf = [0; 1024];
+    assert_eq!(stream.read(&mut buf).unwrap(), 0);
+    assert_eq!(handle.open_connections(), 0);
+    assert_eq!(handle.in_flight_requests(), 0);
+}
+
+#[test]
+fn handle_shutdown_timeout_drained() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::any().map(|| "hello");
+
+    let (handle, server) = warp::serve(routes)
+        .bind_with_handle(([127, 0, 0, 1], 0));
+
+    let (tx, rx) = oneshot::channel();
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server.then(move |_| tx.send(())));
+
+    handle.shutdown_timeout(Duration::from_secs(30));
+
+    rt.block_on(Timeout::new(rx, Duration::from_secs(5))).unwrap();
+
+    // Nothing was left to drain, so the timer shouldn't keep it running.
+    let start = Instant::now();
+    rt.shutdown_on_idle().wait().unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5), "runtime waited for the shutdown timer");
+}
+
+#[test]
+fn bind_listener() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::addr::local()
+        .map(|addr: Option<SocketAddr>| addr.unwrap().to_string());
+
+    let listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+    let bound = listener.local_addr().unwrap();
+
+    let (addr, server) = warp::serve(routes)
+        .bind_listener(listener);
+    assert_eq!(addr, bound);
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+
+    let res = raw_request(addr, b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n");
+    assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{}", res);
+    assert!(res.ends_with(&addr.to_string()), "{}", res);
+}
+
+#[test]
+fn bind_many() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::addr::listener()
+        .map(|addr: Option<SocketAddr>| addr.unwrap().to_string());
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let addrs = vec![
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+        SocketAddr::from(([127, 0, 0, 1], 0)),
+    ];
+    let (addrs, server) = warp::serve(routes)
+        .bind_many_with_graceful_shutdown(addrs, rx.map_err(|_| ()));
+    assert_eq!(addrs.len(), 2);
+    assert_ne!(addrs[0], addrs[1]);
+
+    let (done_tx, done_rx) = oneshot::channel();
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server.then(move |_| done_tx.send(())));
+
+    for addr in &addrs {
+        let res = raw_request(*addr, b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n");
+        assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{}", res);
+        assert!(res.ends_with(&addr.to_string()), "{}", res);
+    }
+
+    tx.send(()).unwrap();
+    rt.block_on(Timeout::new(done_rx, Duration::from_secs(5))).unwrap();
+    for addr in &addrs {
+        assert!(TcpStream::connect(addr).is_err());
+    }
+}
+
+#[test]
+fn proxy_protocol() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::addr::remote()
+        .and(warp::addr::local())
+        .map(|remote: Option<SocketAddr>, local: Option<SocketAddr>| {
+            format!("{} {}", remote.unwrap(), local.unwrap())
+        });
+
+    let (addr, server) = warp::serve(routes)
+        .proxy_protocol(true)
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+
+    let req = b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n";
+
+    // version 1
+    let mut v1 = b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 443\r\n".to_vec();
+    v1.extend_from_slice(req);
+    let res = raw_request(addr, &v1);
+    assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{}", res);
+    assert!(res.ends_with("192.168.0.1:56324 192.168.0.11:443"), "{}", res);
+
+    // version 2
+    let mut v2 = b"\r\n\r\n\0\r\nQUIT\n\x21\x11\x00\x0c".to_vec();
+    v2.extend_from_slice(&[10, 0, 0, 1, 10, 0, 0, 2, 0x1f, 0x90, 0x01, 0xbb]);
+    v2.extend_from_slice(req);
+    let res = raw_request(addr, &v2);
+    assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{}", res);
+    assert!(res.ends_with("10.0.0.1:8080 10.0.0.2:443"), "{}", res);
+
+    // LOCAL keeps the real addresses
+    let mut local = b"\r\n\r\n\0\r\nQUIT\n\x20\x00\x00\x00".to_vec();
+    local.extend_from_slice(req);
+    let res = raw_request(addr, &local);
+    assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{}", res);
+    assert!(res.ends_with(&addr.to_string()), "{}", res);
+
+    // no header at all
+    let res = raw_request(addr, req);
+    assert_eq!(res, "");
+}
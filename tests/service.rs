@@ -0,0 +1,72 @@
+#![deny(warnings)]
+extern crate futures;
+extern crate hyper;
+extern crate pretty_env_logger;
+extern crate tokio;
+extern crate tower_service;
+extern crate warp;
+
+use futures::{Future, Stream};
+use hyper::{Body, Client, Request};
+use tower_service::Service;
+use warp::Filter;
+
+#[test]
+fn hyper_server() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::path("hello")
+        .and(warp::path::param())
+        .map(|name: String| format!("Hello, {}!", name));
+
+    let svc = warp::service(routes);
+
+    let server = hyper::Server::bind(&([127, 0, 0, 1], 0).into())
+        .serve(move || svc);
+    let addr = server.local_addr();
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server.map_err(|e| panic!("server error: {}", e)));
+
+    let client = Client::new();
+
+    let uri = format!("http://{}/hello/warp", addr).parse::<hyper::Uri>().unwrap();
+    let res = rt.block_on(client.get(uri)).unwrap();
+    assert_eq!(res.status(), 200);
+    let body = rt.block_on(res.into_body().concat2()).unwrap();
+    assert_eq!(&body[..], b"Hello, warp!");
+
+    let uri = format!("http://{}/bye", addr).parse::<hyper::Uri>().unwrap();
+    let res = rt.block_on(client.get(uri)).unwrap();
+    assert_eq!(res.status(), 404);
+}
+
+#[test]
+fn tower_service() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::post2()
+        .and(warp::body::concat())
+        .map(|body: warp::body::FullBody| {
+            let len = warp::Buf::remaining(&body);
+            format!("{} bytes", len)
+        });
+
+    let mut svc = warp::service(routes);
+
+    assert!(svc.poll_ready().unwrap().is_ready());
+
+    let req = Request::post("/")
+        .body(Body::from("hello"))
+        .unwrap();
+    let res = svc.call(req).wait().unwrap();
+    assert_eq!(res.status(), 200);
+    let body = res.into_body().concat2().wait().unwrap();
+    assert_eq!(&body[..], b"5 bytes");
+
+    let req = Request::get("/")
+        .body(Body::empty())
+        .unwrap();
+    let res = svc.call(req).wait().unwrap();
+    assert_eq!(res.status(), 405);
+}
@@ -0,0 +1,103 @@
+#![deny(warnings)]
+extern crate futures;
+extern crate pretty_env_logger;
+extern crate tokio;
+extern crate warp;
+
+use std::io;
+use std::time::{Duration, Instant};
+
+use futures::{stream, Future};
+use tokio::timer::Delay;
+use warp::Filter;
+use warp::sse::Event;
+
+#[test]
+fn events() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any()
+        .map(|| {
+            let events = stream::iter_ok::<_, io::Error>(vec![
+                Event::default().data("line 1\nline 2\r\nline 3"),
+                Event::default()
+                    .event("update")
+                    .id("7")
+                    .retry(Duration::from_millis(2500))
+                    .json_data(&vec![1, 2])
+                    .unwrap(),
+                Event::default().comment("just a comment"),
+            ]);
+            warp::reply::sse(events)
+        });
+
+    let res = warp::test::request().reply(&route);
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-type"], "text/event-stream");
+    assert_eq!(res.headers()["cache-control"], "no-cache");
+    assert_eq!(res.headers()["x-accel-buffering"], "no");
+    assert_eq!(
+        res.body(),
+        "data: line 1\ndata: line 2\ndata: line 3\n\n\
+         event: update\nid: 7\nretry: 2500\ndata: [1,2]\n\n\
+         : just a comment\n\n"
+    );
+}
+
+#[test]
+#[should_panic(expected = "sse event id must not contain a newline")]
+fn event_id_newline() {
+    Event::default().id("1\n2");
+}
+
+#[test]
+fn keep_alive() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any()
+        .map(|| {
+            let late = Delay::new(Instant::now() + Duration::from_millis(100))
+                .map(|()| Event::default().data("late"))
+                .into_stream();
+            warp::reply::sse(late)
+                .keep_alive(Duration::from_millis(20))
+        });
+
+    let res = warp::test::request().reply(&route);
+    assert_eq!(res.status(), 200);
+    let body = String::from_utf8_lossy(res.body());
+    assert!(body.starts_with(":\n\n:\n\n"), "keep-alive comments: {:?}", body);
+    assert!(body.ends_with("\n\ndata: late\n\n"), "then the event: {:?}", body);
+
+    let route = warp::any()
+        .map(|| {
+            let late = Delay::new(Instant::now() + Duration::from_millis(50))
+                .map(|()| Event::default().data("late"))
+                .into_stream();
+            warp::reply::sse(late)
+                .keep_alive(Duration::from_millis(10))
+                .no_keep_alive()
+        });
+
+    let res = warp::test::request().reply(&route);
+    assert_eq!(res.body(), "data: late\n\n");
+}
+
+#[test]
+fn last_event_id() {
+    let _ = pretty_env_logger::try_init();
+
+    let id = warp::sse::last_event_id::<u32>();
+
+    let req = warp::test::request()
+        .header("last-event-id", "42");
+    assert_eq!(req.filter(&id).unwrap(), Some(42));
+
+    let req = warp::test::request();
+    assert_eq!(req.filter(&id).unwrap(), None);
+
+    let res = warp::test::request()
+        .header("last-event-id", "nope")
+        .reply(&id.map(|_| warp::reply()));
+    assert_eq!(res.status(), 400);
+}
@@ -0,0 +1,124 @@
+#![cfg(feature = "tls")]
+#![deny(warnings)]
+extern crate pretty_env_logger;
+extern crate tokio;
+extern crate tokio_rustls;
+extern crate warp;
+
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+
+use tokio_rustls::rustls::{ClientConfig, ClientSession, Session, StreamOwned};
+use tokio_rustls::webpki::DNSNameRef;
+use warp::Filter;
+
+#[test]
+fn tls_hello() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::path("hello")
+        .map(|| "Hello, TLS!");
+
+    let (addr, server) = warp::serve(routes)
+        .tls("examples/tls/cert.pem", "examples/tls/key.rsa")
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+
+    let mut config = ClientConfig::new();
+    let mut ca = BufReader::new(File::open("examples/tls/ca.pem").unwrap());
+    config.root_store.add_pem_file(&mut ca).unwrap();
+
+    let dns_name = DNSNameRef::try_from_ascii_str("localhost").unwrap();
+    let session = ClientSession::new(&Arc::new(config), dns_name);
+    let sock = TcpStream::connect(addr).unwrap();
+    let mut tls = StreamOwned::new(session, sock);
+
+    tls.write_all(b"GET /hello HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n").unwrap();
+
+    let mut buf = Vec::new();
+    // The server may close the socket without a close_notify, so just
+    // take whatever was read before the connection ended.
+    let _ = tls.read_to_end(&mut buf);
+    let resp = String::from_utf8(buf).unwrap();
+
+    assert!(resp.starts_with("HTTP/1.1 200 OK\r\n"), "response: {:?}", resp);
+    assert!(resp.ends_with("\r\n\r\nHello, TLS!"), "response: {:?}", resp);
+}
+
+#[test]
+fn tls_alpn() {
+    let _ = pretty_env_logger::try_init();
+
+    let alpn = |http2_only: bool, offer: &[&str]| {
+        let routes = warp::any().map(warp::reply);
+
+        let (addr, server) = warp::serve(routes)
+            .http2_only(http2_only)
+            .tls("examples/tls/cert.pem", "examples/tls/key.rsa")
+            .bind_ephemeral(([127, 0, 0, 1], 0));
+
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        rt.spawn(server);
+
+        let mut config = ClientConfig::new();
+        let mut ca = BufReader::new(File::open("examples/tls/ca.pem").unwrap());
+        config.root_store.add_pem_file(&mut ca).unwrap();
+        let offer = offer.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        config.set_protocols(&offer);
+
+        let dns_name = DNSNameRef::try_from_ascii_str("localhost").unwrap();
+        let mut session = ClientSession::new(&Arc::new(config), dns_name);
+        let mut sock = TcpStream::connect(addr).unwrap();
+        while session.is_handshaking() {
+            session.complete_io(&mut sock).unwrap();
+        }
+        session.get_alpn_protocol().map(String::from)
+    };
+
+    assert_eq!(alpn(false, &["h2", "http/1.1"]), Some("h2".to_string()));
+    assert_eq!(alpn(false, &["http/1.1"]), Some("http/1.1".to_string()));
+    assert_eq!(alpn(true, &["h2", "http/1.1"]), Some("h2".to_string()));
+}
+
+#[test]
+fn tls_bad_handshake_keeps_serving() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::any()
+        .map(|| "still here");
+
+    let (addr, server) = warp::serve(routes)
+        .tls("examples/tls/cert.pem", "examples/tls/key.rsa")
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+
+    // Not a TLS client hello...
+    let mut plain = TcpStream::connect(addr).unwrap();
+    plain.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+    let mut buf = Vec::new();
+    let _ = plain.read_to_end(&mut buf);
+
+    // ... but the server should still accept proper TLS connections.
+    let mut config = ClientConfig::new();
+    let mut ca = BufReader::new(File::open("examples/tls/ca.pem").unwrap());
+    config.root_store.add_pem_file(&mut ca).unwrap();
+
+    let dns_name = DNSNameRef::try_from_ascii_str("localhost").unwrap();
+    let session = ClientSession::new(&Arc::new(config), dns_name);
+    let sock = TcpStream::connect(addr).unwrap();
+    let mut tls = StreamOwned::new(session, sock);
+
+    tls.write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n").unwrap();
+
+    let mut buf = Vec::new();
+    let _ = tls.read_to_end(&mut buf);
+    let resp = String::from_utf8(buf).unwrap();
+
+    assert!(resp.ends_with("still here"), "response: {:?}", resp);
+}
@@ -0,0 +1,110 @@
+#![cfg(unix)]
+#![deny(warnings)]
+extern crate libc;
+extern crate pretty_env_logger;
+extern crate tokio;
+extern crate warp;
+
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+
+use warp::Filter;
+use warp::unix::PeerCred;
+
+fn sock_path(name: &str) -> PathBuf {
+    let path = ::std::env::temp_dir()
+        .join(format!("warp-test-{}-{}.sock", name, ::std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn get(path: &PathBuf) -> String {
+    let mut sock = UnixStream::connect(path).unwrap();
+    sock.write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n").unwrap();
+    let mut buf = String::new();
+    sock.read_to_string(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn bind_unix_peer_cred() {
+    let _ = pretty_env_logger::try_init();
+
+    let path = sock_path("peer-cred");
+
+    let routes = warp::unix::peer_cred()
+        .map(|cred: Option<PeerCred>| {
+            let cred = cred.expect("unix socket should have peer credentials");
+            format!("{} {} {:?}", cred.uid(), cred.gid(), cred.pid())
+        });
+
+    let server = warp::serve(routes)
+        .unix_socket_mode(0o600)
+        .bind_unix(&path);
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    let resp = get(&path);
+    let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+    let pid = if cfg!(any(target_os = "linux", target_os = "android")) {
+        Some(::std::process::id() as libc::pid_t)
+    } else {
+        None
+    };
+    let expected = format!("{} {} {:?}", uid, gid, pid);
+    assert!(resp.starts_with("HTTP/1.1 200 OK\r\n"), "response: {:?}", resp);
+    assert!(resp.ends_with(&expected), "response: {:?}", resp);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn bind_unix_removes_stale_socket() {
+    let _ = pretty_env_logger::try_init();
+
+    let path = sock_path("stale");
+
+    // Leaves a socket file behind, with nothing listening on it.
+    drop(UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+
+    let routes = warp::any().map(|| "fresh");
+    let server = warp::serve(routes)
+        .bind_unix(&path);
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+
+    assert!(get(&path).ends_with("fresh"));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn peer_cred_none_without_unix_socket() {
+    let cred = warp::test::request()
+        .filter(&warp::unix::peer_cred())
+        .unwrap();
+    assert_eq!(cred, None);
+}
+
+#[test]
+fn listen_fds_for_other_process() {
+    let _ = pretty_env_logger::try_init();
+
+    env::set_var("LISTEN_PID", "1");
+    env::set_var("LISTEN_FDS", "1");
+
+    let listeners = warp::listen_fds().unwrap();
+    assert!(listeners.is_empty());
+    assert!(env::var("LISTEN_PID").is_err());
+    assert!(env::var("LISTEN_FDS").is_err());
+}